@@ -94,6 +94,12 @@ pub fn build(b: *std.Build) void {
         "Path to built tables source file",
     );
 
+    const emoji_catalog = b.option(
+        bool,
+        "emoji_catalog",
+        "Build the emoji catalog from `emoji-test.txt` for `uucode.emoji.info` and friends",
+    ) orelse false;
+
     const test_filters = b.option(
         []const []const u8,
         "test-filter",
@@ -129,6 +135,7 @@ pub fn build(b: *std.Build) void {
         .Debug,
         tables_path_opt,
         build_config_path,
+        emoji_catalog,
     );
 
     // b.addModule with an existing module
@@ -149,6 +156,7 @@ pub fn build(b: *std.Build) void {
         .Debug,
         null,
         b.path("src/test/build_config.zig"),
+        true,
     );
 
     const src_tests = b.addTest(.{
@@ -161,6 +169,29 @@ pub fn build(b: *std.Build) void {
         .filters = test_filters,
     });
 
+    // Tests that the library works without the `rgi_emoji_*` fields.
+    const no_rgi_mod = createLibMod(
+        b,
+        target,
+        optimize,
+        .Debug,
+        null,
+        b.path("src/test/build_config_no_rgi.zig"),
+        false,
+    );
+
+    const no_rgi_tests = b.addTest(.{
+        .root_module = b.createModule(.{
+            .root_source_file = b.path("src/test/no_rgi.zig"),
+            .target = target,
+            .optimize = optimize,
+            .imports = &.{
+                .{ .name = "uucode", .module = no_rgi_mod.lib },
+            },
+        }),
+        .filters = test_filters,
+    });
+
     const build_tests = b.addTest(.{
         .root_module = b.createModule(.{
             .root_source_file = b.path("build.zig"),
@@ -173,11 +204,13 @@ pub fn build(b: *std.Build) void {
     const run_src_tests = b.addRunArtifact(src_tests);
     const run_build_tables_tests = b.addRunArtifact(generate_tests);
     const run_build_tests = b.addRunArtifact(build_tests);
+    const run_no_rgi_tests = b.addRunArtifact(no_rgi_tests);
 
     const test_step = b.step("test", "Run tests");
     test_step.dependOn(&run_src_tests.step);
     test_step.dependOn(&run_build_tables_tests.step);
     test_step.dependOn(&run_build_tests.step);
+    test_step.dependOn(&run_no_rgi_tests.step);
 }
 
 fn buildBuildConfig(
@@ -330,6 +363,28 @@ fn generateTables(
     };
 }
 
+// The emoji catalog is keyed by emoji sequence rather than by code point,
+// so it's generated separately from the tables. See `src/emoji_catalog.zig`.
+fn generateEmojiCatalog(b: *std.Build) std.Build.LazyPath {
+    const gen_mod = b.createModule(.{
+        .root_source_file = b.path("src/generate_emoji.zig"),
+        .target = b.graph.host,
+        .optimize = .Debug,
+    });
+    const gen_exe = b.addExecutable(.{
+        .name = "uucode_generate_emoji",
+        .root_module = gen_mod,
+
+        // Zig's x86 backend is segfaulting, so we choose the LLVM backend always.
+        .use_llvm = true,
+    });
+
+    const run_gen_exe = b.addRunArtifact(gen_exe);
+    run_gen_exe.setCwd(b.path(""));
+    run_gen_exe.addFileInput(b.path("ucd/emoji/emoji-test.txt"));
+    return run_gen_exe.addOutputFileArg("emoji_catalog_data.zig");
+}
+
 fn createLibMod(
     b: *std.Build,
     target: std.Build.ResolvedTarget,
@@ -337,6 +392,7 @@ fn createLibMod(
     generate_optimize: std.builtin.OptimizeMode,
     tables_path_opt: ?std.Build.LazyPath,
     build_config_path: std.Build.LazyPath,
+    emoji_catalog: bool,
 ) struct {
     lib: *std.Build.Module,
     build_config: *std.Build.Module,
@@ -401,6 +457,20 @@ fn createLibMod(
     lib_mod.addImport("config.zig", config_mod);
     lib_mod.addImport("tables", tables_mod);
 
+    // The emoji catalog is only generated (from `ucd/emoji/emoji-test.txt`)
+    // with the `emoji_catalog` option. Otherwise `emoji_catalog_data` is a
+    // stub that fails to compile if any of the catalog is used.
+    const emoji_catalog_data_mod = b.createModule(.{
+        .root_source_file = if (emoji_catalog)
+            generateEmojiCatalog(b)
+        else
+            b.path("src/emoji_catalog_disabled.zig"),
+        .target = target,
+        .optimize = optimize,
+    });
+    emoji_catalog_data_mod.addImport("types.zig", types_mod);
+    lib_mod.addImport("emoji_catalog_data", emoji_catalog_data_mod);
+
     return .{
         .lib = lib_mod,
         .build_config = build_config_mod,
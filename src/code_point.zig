@@ -24,6 +24,12 @@ pub const Iterator = struct {
         if (self.i >= self.code_points.len) return null;
         return self.code_points[self.i];
     }
+
+    pub fn prev(self: *Self) ?u21 {
+        if (self.i == 0) return null;
+        self.i -= 1;
+        return self.code_points[self.i];
+    }
 };
 
 test "Iterator for emoji code points" {
@@ -47,6 +53,9 @@ test "Iterator for emoji code points" {
     try std.testing.expectEqual(4, it.i);
     try std.testing.expectEqual(null, it.next());
     try std.testing.expectEqual(4, it.i);
+
+    try std.testing.expectEqual(0x1F47A, it.prev());
+    try std.testing.expectEqual(3, it.i);
 }
 
 /// Returns a custom iterator for a given Context type.
@@ -100,6 +109,23 @@ pub fn CustomIterator(comptime Context: type) type {
             var it = self;
             return it.next();
         }
+
+        pub fn prev(self: *Self) ?u21 {
+            const getFn = @typeInfo(@TypeOf(@TypeOf(self.ctx).get)).@"fn";
+            if (comptime getFn.return_type.? == ?u21) {
+                while (self.i > 0) {
+                    self.i -= 1;
+                    if (self.ctx.get(self.i)) |cp| return cp;
+                }
+            } else {
+                if (self.i > 0) {
+                    self.i -= 1;
+                    return self.ctx.get(self.i);
+                }
+            }
+
+            return null;
+        }
     };
 }
 
@@ -178,4 +204,14 @@ test "CustomIterator for emoji code points with gaps and optional get" {
     try std.testing.expectEqual(5, it.i);
     try std.testing.expectEqual(null, it.next());
     try std.testing.expectEqual(7, it.i);
+
+    try std.testing.expectEqual(0x1F47A, it.prev());
+    try std.testing.expectEqual(4, it.i);
+    try std.testing.expectEqual(0x1F63B, it.prev());
+    try std.testing.expectEqual(3, it.i);
+    try std.testing.expectEqual(0x1F605, it.prev());
+    try std.testing.expectEqual(2, it.i);
+    try std.testing.expectEqual(0x1F600, it.prev());
+    try std.testing.expectEqual(0, it.i);
+    try std.testing.expectEqual(null, it.prev());
 }
@@ -91,6 +91,17 @@ pub const build_components: []const config.Component = &.{
         },
     },
     .{ .Impl = EmojiVs, .fields = &.{"is_emoji_vs_base"} },
+    .{
+        .Impl = EmojiSequences,
+        .fields = &.{
+            "rgi_emoji_basic",
+            "is_rgi_emoji_keycap_base",
+            "rgi_emoji_flag_pairs",
+            "is_rgi_emoji_modifier_base",
+            "rgi_emoji_tag_sequences",
+        },
+    },
+    .{ .Impl = EmojiZwjSequences, .fields = &.{"rgi_emoji_zwj_sequences"} },
     .{ .Impl = BidiPairedBracket, .fields = &.{"bidi_paired_bracket"} },
     .{ .Impl = BidiMirroring, .fields = &.{"bidi_mirroring"} },
     .{ .Impl = Blocks, .fields = &.{"block"} },
@@ -167,12 +178,22 @@ pub const build_components: []const config.Component = &.{
             "east_asian_width",
             "general_category",
             "grapheme_break",
+            "original_grapheme_break",
             "is_default_ignorable",
             "is_emoji_modifier",
+            "is_emoji_presentation",
+            "is_extended_pictographic",
         },
         .fields = &.{
             "wcwidth_standalone",
+            "wcwidth_standalone_cjk",
             "wcwidth_zero_in_grapheme",
+            "compat_wcwidth_c",
+            "compat_wcwidth_py",
+            "compat_go_runewidth",
+            "compat_unicode_width",
+            "compat_uniseg",
+            "compat_utf8proc",
         },
     },
 };
@@ -1243,6 +1264,210 @@ const EmojiVs = struct {
     }
 };
 
+const EmojiSequences = struct {
+    pub fn build(
+        comptime InputRow: type,
+        comptime Row: type,
+        allocator: std.mem.Allocator,
+        io: std.Io,
+        inputs: config.MultiSlice(InputRow),
+        rows: *config.MultiSlice(Row),
+        backing: anytype,
+        tracking: anytype,
+    ) !void {
+        _ = inputs;
+        _ = backing;
+
+        const default_row: Row = comptime blk: {
+            var row: Row = undefined;
+            setBuiltField(&row, "rgi_emoji_basic", .none);
+            setBuiltField(&row, "is_rgi_emoji_keycap_base", false);
+            setBuiltField(&row, "rgi_emoji_flag_pairs", 0);
+            setBuiltField(&row, "is_rgi_emoji_modifier_base", false);
+            setBuiltField(&row, "rgi_emoji_tag_sequences", .empty);
+            break :blk row;
+        };
+
+        rows.len = config.num_code_points;
+        rows.memset(default_row);
+
+        const file_path = "ucd/emoji/emoji-sequences.txt";
+
+        const content = try readFile(allocator, io, file_path);
+        defer allocator.free(content);
+
+        // Indexed by the first regional indicator
+        var flag_pairs: [26]u26 = @splat(0);
+
+        var tag_sequences: std.ArrayList(u21) = .empty;
+        defer tag_sequences.deinit(allocator);
+
+        var lines = std.mem.splitScalar(u8, content, '\n');
+        while (lines.next()) |line| {
+            const trimmed = trim(line);
+            if (trimmed.len == 0) continue;
+
+            var parts = std.mem.splitScalar(u8, trimmed, ';');
+            const cp_str = std.mem.trim(u8, parts.next().?, " \t\r");
+            const type_str = std.mem.trim(u8, parts.next().?, " \t\r");
+
+            const sequence_type = emoji_sequence_type_map.get(type_str) orelse {
+                std.log.err("Unknown emoji sequence type: {s}", .{type_str});
+                if (!config.is_updating_ucd) {
+                    unreachable;
+                } else {
+                    continue;
+                }
+            };
+
+            var cps: [8]u21 = undefined;
+            var cps_len: usize = 0;
+            if (std.mem.indexOf(u8, cp_str, "..") != null) {
+                inlineAssert(sequence_type == .basic);
+                const range = try parseRange(cp_str);
+                for (range.start..range.end) |cp| {
+                    var row = rows.get(cp);
+                    setBuiltField(&row, "rgi_emoji_basic", .single);
+                    rows.set(cp, row);
+                }
+                continue;
+            }
+
+            var cp_parts = std.mem.splitScalar(u8, cp_str, ' ');
+            while (cp_parts.next()) |part| {
+                if (part.len == 0) continue;
+                cps[cps_len] = try parseCp(part);
+                cps_len += 1;
+            }
+
+            const first = cps[0];
+            var row = rows.get(first);
+            switch (sequence_type) {
+                .basic => {
+                    if (cps_len == 1) {
+                        setBuiltField(&row, "rgi_emoji_basic", .single);
+                    } else {
+                        inlineAssert(cps_len == 2 and cps[1] == 0xFE0F);
+                        setBuiltField(&row, "rgi_emoji_basic", .vs16);
+                    }
+                },
+                .keycap => {
+                    inlineAssert(cps_len == 3 and cps[1] == 0xFE0F and cps[2] == 0x20E3);
+                    setBuiltField(&row, "is_rgi_emoji_keycap_base", true);
+                },
+                .flag => {
+                    inlineAssert(cps_len == 2);
+                    const bit = @as(u26, 1) << @intCast(cps[1] - 0x1F1E6);
+                    flag_pairs[first - 0x1F1E6] |= bit;
+                },
+                .tag => {
+                    inlineAssert(first == 0x1F3F4);
+                    try tag_sequences.append(allocator, @intCast(cps_len - 1));
+                    try tag_sequences.appendSlice(allocator, cps[1..cps_len]);
+                },
+                .modifier => {
+                    inlineAssert(cps_len == 2);
+                    setBuiltField(&row, "is_rgi_emoji_modifier_base", true);
+                },
+            }
+            rows.set(first, row);
+        }
+
+        for (flag_pairs, 0x1F1E6..) |pairs, cp| {
+            var row = rows.get(cp);
+            setBuiltField(&row, "rgi_emoji_flag_pairs", pairs);
+            rows.set(cp, row);
+        }
+
+        var row = rows.get(0x1F3F4);
+        try setAllocField(allocator, &row, "rgi_emoji_tag_sequences", 0x1F3F4, tag_sequences.items, tracking);
+        rows.set(0x1F3F4, row);
+    }
+};
+
+const emoji_sequence_type_map = std.StaticStringMap(enum {
+    basic,
+    keycap,
+    flag,
+    tag,
+    modifier,
+}).initComptime(.{
+    .{ "Basic_Emoji", .basic },
+    .{ "Emoji_Keycap_Sequence", .keycap },
+    .{ "RGI_Emoji_Flag_Sequence", .flag },
+    .{ "RGI_Emoji_Tag_Sequence", .tag },
+    .{ "RGI_Emoji_Modifier_Sequence", .modifier },
+});
+
+const EmojiZwjSequences = struct {
+    pub fn build(
+        comptime InputRow: type,
+        comptime Row: type,
+        allocator: std.mem.Allocator,
+        io: std.Io,
+        inputs: config.MultiSlice(InputRow),
+        rows: *config.MultiSlice(Row),
+        backing: anytype,
+        tracking: anytype,
+    ) !void {
+        _ = inputs;
+        _ = backing;
+
+        const default_row: Row = comptime blk: {
+            var row: Row = undefined;
+            setBuiltField(&row, "rgi_emoji_zwj_sequences", .empty);
+            break :blk row;
+        };
+
+        rows.len = config.num_code_points;
+        rows.memset(default_row);
+
+        const file_path = "ucd/emoji/emoji-zwj-sequences.txt";
+
+        const content = try readFile(allocator, io, file_path);
+        defer allocator.free(content);
+
+        // The sequences are grouped by category rather than sorted, so
+        // collect them by first code point before setting the fields.
+        var sequences: std.AutoArrayHashMapUnmanaged(u21, std.ArrayList(u21)) = .empty;
+        defer {
+            for (sequences.values()) |*list| list.deinit(allocator);
+            sequences.deinit(allocator);
+        }
+
+        var lines = std.mem.splitScalar(u8, content, '\n');
+        while (lines.next()) |line| {
+            const trimmed = trim(line);
+            if (trimmed.len == 0) continue;
+
+            var parts = std.mem.splitScalar(u8, trimmed, ';');
+            const cp_str = std.mem.trim(u8, parts.next().?, " \t\r");
+
+            var cps: [16]u21 = undefined;
+            var cps_len: usize = 0;
+            var cp_parts = std.mem.splitScalar(u8, cp_str, ' ');
+            while (cp_parts.next()) |part| {
+                if (part.len == 0) continue;
+                cps[cps_len] = try parseCp(part);
+                cps_len += 1;
+            }
+
+            const entry = try sequences.getOrPut(allocator, cps[0]);
+            if (!entry.found_existing) entry.value_ptr.* = .empty;
+            try entry.value_ptr.append(allocator, @intCast(cps_len - 1));
+            try entry.value_ptr.appendSlice(allocator, cps[1..cps_len]);
+        }
+
+        var it = sequences.iterator();
+        while (it.next()) |entry| {
+            const cp = entry.key_ptr.*;
+            var row = rows.get(cp);
+            try setAllocField(allocator, &row, "rgi_emoji_zwj_sequences", cp, entry.value_ptr.items, tracking);
+            rows.set(cp, row);
+        }
+    }
+};
+
 const BidiPairedBracket = struct {
     pub fn build(
         comptime InputRow: type,
@@ -2694,10 +2919,35 @@ const Wcwidth = struct {
 
             if (cp == 0x20E3) {
                 setBuiltField(&row, "wcwidth_standalone", 2);
+                setBuiltField(&row, "wcwidth_standalone_cjk", 2);
             } else {
                 setBuiltField(&row, "wcwidth_standalone", width);
+
+                // In East Asian (CJK) contexts, East Asian Width (UAX #11)
+                // Ambiguous (A) characters are displayed wide. The soft
+                // hyphen keeps its exceptional width of 1, and combining
+                // marks stay narrow since standing alone they're displayed
+                // over a (narrow) dotted circle or space.
+                if (width == 1 and
+                    cp != 0x00AD and
+                    input.east_asian_width == .ambiguous and
+                    gc != .mark_nonspacing and
+                    gc != .mark_spacing_combining and
+                    gc != .mark_enclosing)
+                {
+                    setBuiltField(&row, "wcwidth_standalone_cjk", 2);
+                } else {
+                    setBuiltField(&row, "wcwidth_standalone_cjk", width);
+                }
             }
 
+            setBuiltField(&row, "compat_wcwidth_c", compatWcwidthC(cp, input));
+            setBuiltField(&row, "compat_wcwidth_py", compatWcwidthPy(cp, input));
+            setBuiltField(&row, "compat_go_runewidth", compatGoRunewidth(cp, input));
+            setBuiltField(&row, "compat_unicode_width", unicodeWidth(cp, input));
+            setBuiltField(&row, "compat_uniseg", compatUniseg(cp, input));
+            setBuiltField(&row, "compat_utf8proc", compatUtf8proc(cp, input));
+
             if (width == 0 or
                 input.is_emoji_modifier or
                 gc == .mark_nonspacing or
@@ -2714,4 +2964,145 @@ const Wcwidth = struct {
             rows.append(row);
         }
     }
+
+    // The following reproduce the per-code-point widths of other wcwidth
+    // implementations (see resources/wcwidth), using the current version of
+    // the UCD rather than the (often older) tables they ship with. Where the
+    // original returns -1 (for C0/C1 control characters), these return 0.
+    //
+    // `resources/wcwidth/zg.zig` and `resources/wcwidth/ziglyph.zig` are out
+    // of scope: they're Zig libraries that can be replaced by this one rather
+    // than widths a host program assumes, and both give backspace and DEL a
+    // width of -1, which a string width can't be matched with per code point.
+
+    // Markus Kuhn's `mk_wcwidth` in `resources/wcwidth/wcwidth.c`, whose
+    // rules are also followed by glibc and musl.
+    fn compatWcwidthC(cp: u21, input: anytype) u2 {
+        const gc = input.general_category;
+        if (gc == .other_control) return 0;
+        if (gc == .mark_nonspacing or gc == .mark_enclosing) return 0;
+        if (gc == .other_format and cp != 0x00AD) return 0;
+        if (cp == 0x200B) return 0;
+        if (cp >= 0x1160 and cp <= 0x11FF) return 0;
+        if (input.east_asian_width == .wide or input.east_asian_width == .fullwidth) return 2;
+        return 1;
+    }
+
+    // `wcwidth` in `resources/wcwidth/wcwidth.py` (jquast/wcwidth), which
+    // is used by many Python TUI libraries.
+    fn compatWcwidthPy(cp: u21, input: anytype) u2 {
+        const gc = input.general_category;
+        if (cp == 0x00AD) return 1;
+        if (gc == .other_control) return 0;
+        if (gc == .mark_nonspacing or
+            gc == .mark_spacing_combining or
+            gc == .mark_enclosing or
+            gc == .other_format or
+            gc == .separator_line or
+            gc == .separator_paragraph) return 0;
+        if ((cp >= 0x1160 and cp <= 0x11FF) or (cp >= 0xD7B0 and cp <= 0xD7FF)) return 0;
+        if (input.is_emoji_modifier) return 0;
+        if (input.east_asian_width == .wide or input.east_asian_width == .fullwidth) return 2;
+        return 1;
+    }
+
+    // `RuneWidth` in `resources/wcwidth/go_runewidth.go` with
+    // `EastAsianWidth` disabled (the default outside of CJK locales).
+    fn compatGoRunewidth(cp: u21, input: anytype) u2 {
+        const gc = input.general_category;
+        if (cp < 0x20) return 0;
+        if ((cp >= 0x7F and cp <= 0x9F) or cp == 0x00AD) return 0;
+        if (cp < 0x300) return 1;
+        if (input.east_asian_width == .narrow) return 1;
+        if (gc == .other_control or
+            gc == .other_format or
+            gc == .other_surrogate or
+            gc == .separator_line or
+            gc == .separator_paragraph or
+            gc == .mark_nonspacing or
+            gc == .mark_spacing_combining or
+            gc == .mark_enclosing) return 0;
+        if (input.east_asian_width == .wide or input.east_asian_width == .fullwidth) return 2;
+        return 1;
+    }
+
+    // The width of a code point within a string according to
+    // `resources/wcwidth/unicode_width.rs` (rule 2 of "Rules for determining
+    // width"), before applying any of its sequence rules, which are handled
+    // by the `unicode_width` option of `grapheme.wcwidthNextOptions`. Note
+    // that unicode-width gives control characters a width of 1 within a
+    // string (with "\r\n" being 1 in total).
+    fn unicodeWidth(cp: u21, input: anytype) u2 {
+        switch (cp) {
+            0x2D7F => return 1, // TIFINAGH CONSONANT JOINER
+            0x115F, 0x17A4 => return 2, // HANGUL CHOSEONG FILLER, KHMER INDEPENDENT VOWEL QAA
+            0x17D8 => return 3, // KHMER SIGN BEYYAL
+            0xA8FA => return 0, // DEVANAGARI CARET
+            else => {},
+        }
+
+        if (input.general_category == .other_control) return 1;
+        if (input.is_default_ignorable) return 0;
+
+        switch (input.grapheme_break) {
+            // Grapheme_Extend, and Hangul_Syllable_Type V or T
+            .zwnj,
+            .indic_conjunct_break_extend,
+            .indic_conjunct_break_linker,
+            .v,
+            .t,
+            => return 0,
+            .prepend => switch (cp) {
+                // Prepended_Concatenation_Mark characters not in the list
+                // of those with width 0.
+                0x0600...0x0604, 0x06DD, 0x110BD, 0x110CD => {},
+                else => return 0,
+            },
+            else => {},
+        }
+
+        if (input.east_asian_width == .wide or input.east_asian_width == .fullwidth) return 2;
+        return 1;
+    }
+
+    // `runeWidth` in `resources/wcwidth/uniseg.go` (rivo/uniseg, used by
+    // tview and many other Go TUI libraries), with
+    // `EastAsianAmbiguousWidth` at its default of 1. Its grapheme cluster
+    // rules are in `grapheme.utf8WcwidthCompat`.
+    fn compatUniseg(cp: u21, input: anytype) u3 {
+        switch (input.original_grapheme_break) {
+            .control, .cr, .lf, .extend, .zwj => return 0,
+            .regional_indicator => return 2,
+            else => {},
+        }
+        if (input.is_extended_pictographic) {
+            return if (input.is_emoji_presentation) 2 else 1;
+        }
+        if (cp == 0x2E3A) return 3;
+        if (cp == 0x2E3B) return 4;
+        if (input.east_asian_width == .wide or input.east_asian_width == .fullwidth) return 2;
+        return 1;
+    }
+
+    // `derive_char_width` in `resources/wcwidth/utf8proc.jl` (utf8proc's
+    // `utf8proc_charwidth`, which is also Julia's `textwidth`).
+    fn compatUtf8proc(cp: u21, input: anytype) u2 {
+        const gc = input.general_category;
+        if (cp == 0x00AD) return 1;
+        if (cp == 0x2028 or cp == 0x2029) return 0;
+        if (gc == .mark_nonspacing) return 0;
+        switch (input.east_asian_width) {
+            .wide, .fullwidth => return 2,
+            .narrow, .halfwidth, .ambiguous => return 1,
+            .neutral => {},
+        }
+        if (gc == .mark_spacing_combining or
+            gc == .mark_enclosing or
+            gc == .separator_line or
+            gc == .separator_paragraph or
+            gc == .other_control or
+            gc == .other_format or
+            gc == .other_surrogate) return 0;
+        return 1;
+    }
 };
@@ -0,0 +1,400 @@
+const std = @import("std");
+
+const getpkg = @import("get.zig");
+const grapheme = @import("grapheme.zig");
+const utf8 = @import("utf8.zig");
+const emoji_catalog = @import("emoji_catalog.zig");
+const get = getpkg.get;
+
+// The kinds of RGI ("recommended for general interchange") emoji from
+// UTS #51, which are the sequences that fonts and keyboards are expected to
+// support.
+pub const RgiKind = enum {
+    // A single code point, or a text-default one followed by U+FE0F
+    basic,
+
+    // [0-9#*] FE0F 20E3
+    keycap,
+
+    // A pair of regional indicators
+    flag,
+
+    // U+1F3F4 followed by tag characters and U+E007F CANCEL TAG
+    tag,
+
+    // An emoji modifier base followed by a skin tone modifier
+    modifier,
+
+    // Emoji joined by U+200D ZERO WIDTH JOINER, such as 👩‍🚀
+    zwj,
+};
+
+const zwj = 0x200D;
+const vs15 = 0xFE0E;
+const vs16 = 0xFE0F;
+const combining_enclosing_keycap = 0x20E3;
+const waving_black_flag = 0x1F3F4;
+const regional_indicator_a = 0x1F1E6;
+const tag_base = 0xE0000;
+const cancel_tag = 0xE007F;
+
+fn isRegionalIndicator(cp: u21) bool {
+    return cp >= regional_indicator_a and cp <= 0x1F1FF;
+}
+
+pub const isEmojiModifier = @import("emoji_modifier.zig").isEmojiModifier;
+
+// Whether `tail` is one of the sequences in `sequences`, each stored as its
+// length followed by its code points.
+fn containsSequence(sequences: []const u21, tail: []const u21) bool {
+    var i: usize = 0;
+    while (i < sequences.len) {
+        const len = sequences[i];
+        if (std.mem.eql(u21, sequences[i + 1 ..][0..len], tail)) return true;
+        i += 1 + len;
+    }
+    return false;
+}
+
+// Returns the kind of RGI emoji that `cps` is exactly, or null if it isn't
+// one. This tells a real emoji like 👩‍🚀 apart from an arbitrary sequence of
+// emoji and ZWJs that fonts will render as separate glyphs.
+//
+// Only fully-qualified sequences are RGI, so for example ❤ (U+2764) alone is
+// not, but ❤️ (U+2764 U+FE0F) is.
+pub fn rgiKind(cps: []const u21) ?RgiKind {
+    if (cps.len == 0) return null;
+    const first = cps[0];
+
+    if (std.mem.indexOfScalar(u21, cps, zwj) != null) {
+        const sequences = get(.rgi_emoji_zwj_sequences, first);
+        return if (containsSequence(sequences, cps[1..])) .zwj else null;
+    }
+
+    if (cps.len == 1) {
+        return if (get(.rgi_emoji_basic, first) == .single) .basic else null;
+    }
+
+    if (cps.len == 2) {
+        const second = cps[1];
+        if (second == vs16) {
+            return if (get(.rgi_emoji_basic, first) == .vs16) .basic else null;
+        } else if (isEmojiModifier(second)) {
+            return if (get(.is_rgi_emoji_modifier_base, first)) .modifier else null;
+        } else if (isRegionalIndicator(first) and isRegionalIndicator(second)) {
+            const bit = @as(u26, 1) << @intCast(second - regional_indicator_a);
+            return if (get(.rgi_emoji_flag_pairs, first) & bit != 0) .flag else null;
+        }
+        return null;
+    }
+
+    if (cps.len == 3 and cps[1] == vs16 and cps[2] == combining_enclosing_keycap) {
+        return if (get(.is_rgi_emoji_keycap_base, first)) .keycap else null;
+    }
+
+    if (first == waving_black_flag) {
+        const sequences = get(.rgi_emoji_tag_sequences, first);
+        return if (containsSequence(sequences, cps[1..])) .tag else null;
+    }
+
+    return null;
+}
+
+// Whether `cps` is exactly one RGI emoji. See `rgiKind`.
+pub fn isRgi(cps: []const u21) bool {
+    return rgiKind(cps) != null;
+}
+
+// Returns the flag for the ISO 3166-1 alpha-2 region code `region` (ignoring
+// ASCII case), such as "CH" for 🇨🇭, or null if it isn't an RGI flag.
+pub fn flagFromRegion(region: []const u8) ?[2]u21 {
+    if (region.len != 2 or
+        !std.ascii.isAlphabetic(region[0]) or
+        !std.ascii.isAlphabetic(region[1])) return null;
+
+    const flag: [2]u21 = .{
+        regional_indicator_a + @as(u21, std.ascii.toUpper(region[0]) - 'A'),
+        regional_indicator_a + @as(u21, std.ascii.toUpper(region[1]) - 'A'),
+    };
+    return if (rgiKind(&flag) == .flag) flag else null;
+}
+
+// Returns the (uppercase) region code of the flag `cps`, such as "CH" for
+// 🇨🇭, or null if it isn't an RGI flag.
+pub fn regionFromFlag(cps: []const u21) ?[2]u8 {
+    if (rgiKind(cps) != .flag) return null;
+    return .{
+        @intCast('A' + (cps[0] - regional_indicator_a)),
+        @intCast('A' + (cps[1] - regional_indicator_a)),
+    };
+}
+
+// The longest Unicode subdivision ID, such as "gbsct" for Scotland, which
+// is a region code followed by one to four letters or digits.
+pub const max_subdivision_len = 6;
+
+// Writes the subdivision flag (U+1F3F4, the tag characters of
+// `subdivision`, then U+E007F CANCEL TAG) for `subdivision` (ignoring ASCII
+// case) to `dest`, returning null if it isn't an RGI flag.
+pub fn flagFromSubdivision(
+    dest: *[max_subdivision_len + 2]u21,
+    subdivision: []const u8,
+) ?[]const u21 {
+    if (subdivision.len < 3 or subdivision.len > max_subdivision_len) return null;
+
+    dest[0] = waving_black_flag;
+    for (subdivision, dest[1..][0..subdivision.len]) |c, *cp| {
+        if (!std.ascii.isAlphanumeric(c)) return null;
+        cp.* = tag_base + @as(u21, std.ascii.toLower(c));
+    }
+    dest[subdivision.len + 1] = cancel_tag;
+
+    const flag = dest[0 .. subdivision.len + 2];
+    return if (rgiKind(flag) == .tag) flag else null;
+}
+
+// Writes the (lowercase) subdivision ID of the flag `cps` to `dest`, such as
+// "gbsct" for 🏴󠁧󠁢󠁳󠁣󠁴󠁿, returning null if it isn't an RGI subdivision flag.
+pub fn subdivisionFromFlag(dest: *[max_subdivision_len]u8, cps: []const u21) ?[]const u8 {
+    if (rgiKind(cps) != .tag) return null;
+
+    const tags = cps[1 .. cps.len - 1];
+    for (tags, dest[0..tags.len]) |tag, *c| {
+        c.* = @intCast(tag - tag_base);
+    }
+    return dest[0..tags.len];
+}
+
+pub const Presentation = grapheme.Presentation;
+
+// A formatter that makes the presentation of emoji in `str` explicit, so
+// their width doesn't depend on the terminal or font. See
+// `normalizePresentation`.
+pub const NormalizePresentation = struct {
+    str: []const u8,
+    presentation: Presentation,
+
+    // Returns the selector to add after the variation sequence base `cp`,
+    // if any.
+    fn selectorFor(self: NormalizePresentation, prev: ?u21, cp: u21, next: ?u21) ?[]const u8 {
+        // Modifiers replace the selector, as in ☝🏻.
+        if (next != null and isEmojiModifier(next.?)) return null;
+
+        // Digits, '#' and '*' are only emoji as part of a keycap.
+        if (cp < 0x80 and next != combining_enclosing_keycap) return null;
+
+        switch (self.presentation) {
+            .emoji => {
+                if (get(.is_emoji_presentation, cp)) return null;
+                return "\u{FE0F}";
+            },
+            .text => {
+                if (!get(.is_emoji_presentation, cp)) return null;
+
+                // Keep ZWJ sequences intact.
+                if (prev == zwj or next == zwj) return null;
+                return "\u{FE0E}";
+            },
+        }
+    }
+
+    pub fn format(self: NormalizePresentation, writer: *std.Io.Writer) std.Io.Writer.Error!void {
+        var it = utf8.Iterator.init(self.str);
+        var prev: ?u21 = null;
+
+        // Whether a selector may follow, which is only right after a
+        // variation sequence base that doesn't have one yet.
+        var selector_allowed = false;
+
+        while (true) {
+            const start = it.i;
+            const cp = it.next() orelse break;
+            const bytes = self.str[start..it.i];
+
+            if (cp == vs15 or cp == vs16) {
+                if (selector_allowed) {
+                    try writer.writeAll(bytes);
+                    selector_allowed = false;
+                }
+                continue;
+            }
+
+            try writer.writeAll(bytes);
+            selector_allowed = false;
+
+            if (get(.is_emoji_vs_base, cp)) {
+                const next = it.peek();
+                if (next == vs15 or next == vs16) {
+                    selector_allowed = true;
+                } else if (self.selectorFor(prev, cp, next)) |selector| {
+                    try writer.writeAll(selector);
+                }
+            }
+
+            prev = cp;
+        }
+    }
+};
+
+// Returns a formatter (for "{f}") that adds U+FE0F (for `.emoji`) after each
+// variation sequence base that defaults to text presentation, or U+FE0E (for
+// `.text`) after each that defaults to emoji presentation. Existing
+// selectors are kept, but redundant ones (repeated, or not following a
+// variation sequence base) are removed. Digits, '#' and '*' only get a
+// selector in keycaps, and no selector is added before a skin tone modifier
+// or, for `.text`, within a ZWJ sequence.
+pub fn normalizePresentation(str: []const u8, presentation: Presentation) NormalizePresentation {
+    return .{ .str = str, .presentation = presentation };
+}
+
+// The emoji catalog, which requires the `emoji_catalog` build option. See
+// `emoji_catalog.zig`.
+pub const Status = emoji_catalog.Status;
+pub const Version = emoji_catalog.Version;
+pub const Info = emoji_catalog.Info;
+pub const info = emoji_catalog.info;
+pub const CatalogIterator = emoji_catalog.CatalogIterator;
+pub const catalogIterator = emoji_catalog.catalogIterator;
+pub const Match = emoji_catalog.Match;
+pub const SearchIterator = emoji_catalog.SearchIterator;
+pub const search = emoji_catalog.search;
+pub const Shortcode = emoji_catalog.Shortcode;
+pub const shortcode = emoji_catalog.shortcode;
+pub const fromShortcode = emoji_catalog.fromShortcode;
+pub const FromShortcodes = emoji_catalog.FromShortcodes;
+pub const fromShortcodes = emoji_catalog.fromShortcodes;
+pub const ToShortcodes = emoji_catalog.ToShortcodes;
+pub const toShortcodes = emoji_catalog.toShortcodes;
+pub const SkinTone = emoji_catalog.SkinTone;
+pub const SkinToneVariantIterator = emoji_catalog.SkinToneVariantIterator;
+pub const skinToneVariants = emoji_catalog.skinToneVariants;
+pub const replaceSkinTones = emoji_catalog.replaceSkinTones;
+pub const applySkinTone = emoji_catalog.applySkinTone;
+pub const stripSkinTones = emoji_catalog.stripSkinTones;
+
+test "rgiKind basic" {
+    try std.testing.expectEqual(.basic, rgiKind(&.{0x231A}).?); // ⌚
+    try std.testing.expectEqual(.basic, rgiKind(&.{0x1F600}).?); // 😀
+    try std.testing.expectEqual(.basic, rgiKind(&.{ 0x00A9, vs16 }).?); // ©️
+    try std.testing.expectEqual(null, rgiKind(&.{0x00A9}));
+    try std.testing.expectEqual(null, rgiKind(&.{'a'}));
+    try std.testing.expectEqual(null, rgiKind(&.{ 'a', vs16 }));
+    try std.testing.expectEqual(null, rgiKind(&.{}));
+}
+
+test "rgiKind keycap" {
+    try std.testing.expectEqual(.keycap, rgiKind(&.{ '1', vs16, combining_enclosing_keycap }).?);
+    try std.testing.expectEqual(.keycap, rgiKind(&.{ '#', vs16, combining_enclosing_keycap }).?);
+    try std.testing.expectEqual(null, rgiKind(&.{ '1', combining_enclosing_keycap }));
+    try std.testing.expectEqual(null, rgiKind(&.{ 'a', vs16, combining_enclosing_keycap }));
+}
+
+test "rgiKind flag" {
+    try std.testing.expectEqual(.flag, rgiKind(&.{ 0x1F1FA, 0x1F1F8 }).?); // 🇺🇸
+    try std.testing.expectEqual(.flag, rgiKind(&.{ 0x1F1EF, 0x1F1F5 }).?); // 🇯🇵
+    try std.testing.expectEqual(null, rgiKind(&.{ 0x1F1FA, 0x1F1FA }));
+    try std.testing.expectEqual(null, rgiKind(&.{0x1F1FA}));
+}
+
+test "rgiKind tag" {
+    // 🏴󠁧󠁢󠁳󠁣󠁴󠁿 (Scotland)
+    try std.testing.expectEqual(.tag, rgiKind(&.{ 0x1F3F4, 0xE0067, 0xE0062, 0xE0073, 0xE0063, 0xE0074, 0xE007F }).?);
+    // Valid subdivision syntax, but not RGI
+    try std.testing.expectEqual(null, rgiKind(&.{ 0x1F3F4, 0xE0075, 0xE0073, 0xE0063, 0xE0061, 0xE007F }));
+    try std.testing.expectEqual(.basic, rgiKind(&.{0x1F3F4}).?);
+}
+
+test "rgiKind modifier" {
+    try std.testing.expectEqual(.modifier, rgiKind(&.{ 0x1F44D, 0x1F3FD }).?); // 👍🏽
+    try std.testing.expectEqual(null, rgiKind(&.{ 0x1F600, 0x1F3FD }));
+    try std.testing.expectEqual(.basic, rgiKind(&.{0x1F3FD}).?);
+}
+
+test "rgiKind zwj" {
+    try std.testing.expectEqual(.zwj, rgiKind(&.{ 0x1F469, zwj, 0x1F680 }).?); // 👩‍🚀
+    try std.testing.expectEqual(.zwj, rgiKind(&.{ 0x1F469, 0x1F3FD, zwj, 0x1F680 }).?); // 👩🏽‍🚀
+    try std.testing.expectEqual(.zwj, rgiKind(&.{ 0x1F3F3, vs16, zwj, 0x1F308 }).?); // 🏳️‍🌈
+    try std.testing.expectEqual(null, rgiKind(&.{ 0x1F600, zwj, 0x1F680 }));
+    try std.testing.expectEqual(null, rgiKind(&.{ 0x1F469, zwj }));
+    try std.testing.expectEqual(null, rgiKind(&.{ 0x1F469, zwj, 0x1F680, zwj }));
+    try std.testing.expect(isRgi(&.{ 0x1F469, zwj, 0x1F680 }));
+    try std.testing.expect(!isRgi(&.{ 0x1F680, zwj, 0x1F469 }));
+}
+
+test "flagFromRegion and regionFromFlag" {
+    try std.testing.expectEqual([2]u21{ 0x1F1E8, 0x1F1ED }, flagFromRegion("CH").?);
+    try std.testing.expectEqual([2]u21{ 0x1F1E8, 0x1F1ED }, flagFromRegion("ch").?);
+    try std.testing.expectEqual(null, flagFromRegion("UU"));
+    try std.testing.expectEqual(null, flagFromRegion("C"));
+    try std.testing.expectEqual(null, flagFromRegion("C1"));
+
+    try std.testing.expectEqualStrings("JP", &regionFromFlag(&.{ 0x1F1EF, 0x1F1F5 }).?);
+    try std.testing.expectEqual(null, regionFromFlag(&.{ 0x1F1FA, 0x1F1FA }));
+    try std.testing.expectEqual(null, regionFromFlag(&.{0x1F1FA}));
+}
+
+test "flagFromSubdivision and subdivisionFromFlag" {
+    const scotland = [_]u21{ 0x1F3F4, 0xE0067, 0xE0062, 0xE0073, 0xE0063, 0xE0074, 0xE007F };
+
+    var flag_buffer: [max_subdivision_len + 2]u21 = undefined;
+    try std.testing.expectEqualSlices(u21, &scotland, flagFromSubdivision(&flag_buffer, "gbsct").?);
+    try std.testing.expectEqualSlices(u21, &scotland, flagFromSubdivision(&flag_buffer, "GBSCT").?);
+    try std.testing.expectEqual(null, flagFromSubdivision(&flag_buffer, "usca"));
+    try std.testing.expectEqual(null, flagFromSubdivision(&flag_buffer, "gb-sct"));
+    try std.testing.expectEqual(null, flagFromSubdivision(&flag_buffer, "gbsctxx"));
+
+    var buffer: [max_subdivision_len]u8 = undefined;
+    try std.testing.expectEqualStrings("gbsct", subdivisionFromFlag(&buffer, &scotland).?);
+    try std.testing.expectEqual(null, subdivisionFromFlag(&buffer, &.{0x1F3F4}));
+}
+
+// Also used by the tests in `emoji_catalog.zig`.
+pub fn testFormat(expected: []const u8, value: anytype) !void {
+    var buffer: [128]u8 = undefined;
+    const actual = try std.fmt.bufPrint(&buffer, "{f}", .{value});
+    try std.testing.expectEqualStrings(expected, actual);
+}
+
+test "normalizePresentation emoji" {
+    // ❤ and © default to text presentation, ⌚ to emoji presentation
+    try testFormat("\u{2764}\u{FE0F} \u{00A9}\u{FE0F} \u{231A}", normalizePresentation("\u{2764} \u{00A9} \u{231A}", .emoji));
+
+    // Existing selectors are kept, and redundant ones removed
+    try testFormat("\u{2764}\u{FE0E}", normalizePresentation("\u{2764}\u{FE0E}", .emoji));
+    try testFormat("\u{2764}\u{FE0F}", normalizePresentation("\u{2764}\u{FE0F}\u{FE0F}", .emoji));
+    try testFormat("a", normalizePresentation("a\u{FE0F}", .emoji));
+
+    // Keycaps, modifiers and ZWJ sequences
+    try testFormat("#1 1\u{FE0F}\u{20E3}", normalizePresentation("#1 1\u{20E3}", .emoji));
+    try testFormat("\u{261D}\u{1F3FB}", normalizePresentation("\u{261D}\u{1F3FB}", .emoji));
+    try testFormat(
+        "\u{1F468}\u{200D}\u{2764}\u{FE0F}\u{200D}\u{1F468}",
+        normalizePresentation("\u{1F468}\u{200D}\u{2764}\u{200D}\u{1F468}", .emoji),
+    );
+}
+
+test "normalizePresentation text" {
+    try testFormat("\u{2764} \u{231A}\u{FE0E}", normalizePresentation("\u{2764} \u{231A}", .text));
+    try testFormat("\u{231A}\u{FE0F}", normalizePresentation("\u{231A}\u{FE0F}", .text));
+
+    // 🏃‍➡️ keeps its selector, and ➡ isn't emoji presentation anyway,
+    // while ⌚ within a ZWJ sequence is left alone
+    try testFormat(
+        "\u{1F3C3}\u{200D}\u{27A1}\u{FE0F}",
+        normalizePresentation("\u{1F3C3}\u{200D}\u{27A1}\u{FE0F}", .text),
+    );
+    try testFormat("\u{231A}\u{200D}x", normalizePresentation("\u{231A}\u{200D}x", .text));
+}
+
+test "normalizePresentation stable width" {
+    var buffer: [64]u8 = undefined;
+    const s = "\u{2764} \u{231A}";
+    const emoji_str = try std.fmt.bufPrint(&buffer, "{f}", .{normalizePresentation(s, .emoji)});
+    try std.testing.expectEqual(5, grapheme.utf8Wcwidth(emoji_str));
+    const text_str = try std.fmt.bufPrint(&buffer, "{f}", .{normalizePresentation(s, .text)});
+    try std.testing.expectEqual(3, grapheme.utf8Wcwidth(text_str));
+}
+
+test {
+    _ = emoji_catalog;
+}
@@ -0,0 +1,561 @@
+const std = @import("std");
+
+const types = @import("types.zig");
+const grapheme = @import("grapheme.zig");
+const utf8 = @import("utf8.zig");
+const emoji = @import("emoji.zig");
+const catalog = @import("emoji_catalog_data");
+
+// The emoji catalog from `emoji-test.txt`: names, groups, versions,
+// shortcodes and skin tone variants of every emoji, re-exported from
+// `uucode.emoji`. The catalog is keyed by emoji sequence rather than by code
+// point, so it's generated separately from the tables, and only with the
+// `emoji_catalog` build option.
+
+const zwj = 0x200D;
+const vs16 = 0xFE0F;
+const combining_enclosing_keycap = 0x20E3;
+
+pub const Status = types.EmojiStatus;
+
+// The Emoji version an emoji was introduced in, such as E0.6 or E17.0
+pub const Version = struct {
+    major: u8,
+    minor: u8,
+};
+
+// An entry from `emoji-test.txt`, which lists every emoji (and the
+// minimally-qualified and unqualified versions of them) in the order they
+// should be shown to users, such as in an emoji picker.
+pub const Info = struct {
+    code_points: []const u21,
+    status: Status,
+    version: Version,
+    group: []const u8,
+    subgroup: []const u8,
+
+    // The CLDR short name, such as "grinning face"
+    name: []const u8,
+};
+
+fn entryInfo(e: catalog.Entry) Info {
+    return .{
+        .code_points = catalog.code_points[e.code_points_offset..][0..e.code_points_len],
+        .status = e.status,
+        .version = .{ .major = e.version_major, .minor = e.version_minor },
+        .group = catalog.groups[e.group],
+        .subgroup = catalog.subgroups[e.subgroup],
+        .name = catalog.names[e.name_offset..][0..e.name_len],
+    };
+}
+
+fn compareEntry(cps: []const u21, i: u16) std.math.Order {
+    const e = catalog.entries[i];
+    return std.mem.order(u21, cps, catalog.code_points[e.code_points_offset..][0..e.code_points_len]);
+}
+
+// Returns the `emoji-test.txt` entry for exactly the sequence `cps`, or null
+// if it isn't listed.
+pub fn info(cps: []const u21) ?Info {
+    return entryInfo(findEntry(cps) orelse return null);
+}
+
+fn findEntry(cps: []const u21) ?catalog.Entry {
+    const i = std.sort.binarySearch(u16, catalog.sorted, cps, compareEntry) orelse return null;
+    return catalog.entries[catalog.sorted[i]];
+}
+
+// Iterates over every entry in `emoji-test.txt`, in file order (which is
+// grouped as in the CLDR emoji collation order).
+pub const CatalogIterator = struct {
+    i: usize = 0,
+
+    pub fn next(self: *CatalogIterator) ?Info {
+        if (self.i == catalog.entries.len) return null;
+        defer self.i += 1;
+        return entryInfo(catalog.entries[self.i]);
+    }
+};
+
+pub fn catalogIterator() CatalogIterator {
+    return .{};
+}
+
+pub const Match = enum {
+    // The query matches the start of a word
+    prefix,
+
+    // The query matches anywhere
+    substring,
+};
+
+// Iterates over the fully-qualified emoji whose name, group or subgroup
+// matches the query (ignoring ASCII case), in catalog order. See `search`.
+pub const SearchIterator = struct {
+    query: []const u8,
+    match: Match,
+    it: CatalogIterator = .{},
+
+    pub fn next(self: *SearchIterator) ?Info {
+        while (self.it.next()) |entry| {
+            if (entry.status != .fully_qualified) continue;
+            if (self.matches(entry.name) or
+                self.matches(entry.subgroup) or
+                self.matches(entry.group)) return entry;
+        }
+        return null;
+    }
+
+    fn matches(self: *const SearchIterator, text: []const u8) bool {
+        switch (self.match) {
+            .substring => return std.ascii.indexOfIgnoreCase(text, self.query) != null,
+            .prefix => {
+                var i: usize = 0;
+                while (std.ascii.indexOfIgnoreCasePos(text, i, self.query)) |pos| {
+                    if (pos == 0 or !std.ascii.isAlphanumeric(text[pos - 1])) return true;
+                    i = pos + 1;
+                }
+                return false;
+            },
+        }
+    }
+};
+
+pub fn search(query: []const u8, match: Match) SearchIterator {
+    return .{ .query = query, .match = match };
+}
+
+// Shortcodes are derived from the CLDR short names: "thumbs up" is
+// `:thumbs_up:`, with runs of spaces and punctuation replaced by a single
+// underscore and ASCII letters lowercased. Emoji with skin tones use the
+// shortcode of the emoji without them followed by a Slack-style
+// `::skin-tone-2` (U+1F3FB) through `::skin-tone-6` (U+1F3FF) suffix, e.g.
+// `:thumbs_up::skin-tone-4:` for 👍🏽 and `:woman_astronaut::skin-tone-4:`
+// for 👩🏽‍🚀, with one suffix per person if their skin tones differ, as in
+// `:people_holding_hands::skin-tone-2::skin-tone-6:`. The few emoji with
+// skin tones but no RGI variant without them, such as 🫱🏻‍🫲🏿, use their own
+// name instead. The shortcodes are generated along with the catalog.
+pub const Shortcode = struct {
+    // The text between the outer colons
+    code: []const u8,
+
+    pub fn format(self: Shortcode, writer: *std.Io.Writer) std.Io.Writer.Error!void {
+        try writer.print(":{s}:", .{self.code});
+    }
+};
+
+const skin_tone_prefix = "::skin-tone-";
+
+fn entryShortcode(e: catalog.Entry) []const u8 {
+    return catalog.shortcodes[e.shortcode_offset..][0..e.shortcode_len];
+}
+
+fn compareShortcode(code: []const u8, i: u16) std.math.Order {
+    return std.mem.order(u8, code, entryShortcode(catalog.entries[i]));
+}
+
+// Returns the shortcode (a formatter for "{f}") for the fully-qualified
+// emoji `cps`, or null if it isn't one.
+pub fn shortcode(cps: []const u21) ?Shortcode {
+    const entry = findEntry(cps) orelse return null;
+    if (entry.status != .fully_qualified) return null;
+    return .{ .code = entryShortcode(entry) };
+}
+
+// Returns the emoji for `code`, the text between the outer colons of a
+// shortcode, such as "thumbs_up" or "thumbs_up::skin-tone-4". See
+// `Shortcode`.
+pub fn fromShortcode(code: []const u8) ?Info {
+    const i = std.sort.binarySearch(u16, catalog.shortcode_index, code, compareShortcode) orelse
+        return null;
+    return entryInfo(catalog.entries[catalog.shortcode_index[i]]);
+}
+
+fn writeCodePoints(writer: *std.Io.Writer, cps: []const u21) std.Io.Writer.Error!void {
+    for (cps) |cp| {
+        var buffer: [4]u8 = undefined;
+        const len = std.unicode.utf8Encode(cp, &buffer) catch unreachable;
+        try writer.writeAll(buffer[0..len]);
+    }
+}
+
+// A formatter that replaces the shortcodes in `str` with their emoji. See
+// `fromShortcodes`.
+pub const FromShortcodes = struct {
+    str: []const u8,
+
+    pub fn format(self: FromShortcodes, writer: *std.Io.Writer) std.Io.Writer.Error!void {
+        var s = self.str;
+        while (std.mem.indexOfScalar(u8, s, ':')) |start| {
+            try writer.writeAll(s[0..start]);
+            const rest = s[start + 1 ..];
+            const end = std.mem.indexOfScalar(u8, rest, ':') orelse {
+                s = s[start..];
+                break;
+            };
+
+            // Include skin tone suffixes, as in ":thumbs_up::skin-tone-4:"
+            var len = end;
+            const suffix_len = skin_tone_prefix.len + "2".len;
+            while (std.mem.startsWith(u8, rest[len..], skin_tone_prefix) and
+                rest.len > len + suffix_len and
+                rest[len + suffix_len] == ':')
+            {
+                len += suffix_len;
+            }
+
+            if (len != 0) {
+                if (fromShortcode(rest[0..len])) |entry| {
+                    try writeCodePoints(writer, entry.code_points);
+                    s = rest[len + 1 ..];
+                    continue;
+                }
+            }
+
+            try writer.writeByte(':');
+            s = rest;
+        }
+        try writer.writeAll(s);
+    }
+};
+
+pub fn fromShortcodes(str: []const u8) FromShortcodes {
+    return .{ .str = str };
+}
+
+// A formatter that replaces the fully-qualified emoji in `str` with their
+// shortcodes. See `toShortcodes`.
+pub const ToShortcodes = struct {
+    str: []const u8,
+
+    pub fn format(self: ToShortcodes, writer: *std.Io.Writer) std.Io.Writer.Error!void {
+        var it = grapheme.utf8Iterator(self.str);
+        while (it.nextGrapheme()) |g| {
+            const bytes = self.str[g.start..g.end];
+
+            // The longest emoji sequences are 10 code points.
+            var cps: [10]u21 = undefined;
+            var len: usize = 0;
+            var cp_it = utf8.Iterator.init(bytes);
+            const code = while (cp_it.next()) |cp| {
+                if (len == cps.len) break null;
+                cps[len] = cp;
+                len += 1;
+            } else shortcode(cps[0..len]);
+
+            if (code) |c| {
+                try c.format(writer);
+            } else {
+                try writer.writeAll(bytes);
+            }
+        }
+    }
+};
+
+pub fn toShortcodes(str: []const u8) ToShortcodes {
+    return .{ .str = str };
+}
+
+// The Fitzpatrick skin tone modifiers
+pub const SkinTone = enum(u21) {
+    light = 0x1F3FB,
+    medium_light = 0x1F3FC,
+    medium = 0x1F3FD,
+    medium_dark = 0x1F3FE,
+    dark = 0x1F3FF,
+};
+
+fn hasSkinTone(cps: []const u21) bool {
+    for (cps) |cp| {
+        if (emoji.isEmojiModifier(cp)) return true;
+    }
+    return false;
+}
+
+// Whether the skin tones of `cps` are `tones`, or are all `tones[0]` if
+// there's only one.
+fn hasSkinTones(cps: []const u21, tones: []const SkinTone) bool {
+    var i: usize = 0;
+    for (cps) |cp| {
+        if (!emoji.isEmojiModifier(cp)) continue;
+        if (tones.len == 1) {
+            if (cp != @intFromEnum(tones[0])) return false;
+        } else {
+            if (i == tones.len or cp != @intFromEnum(tones[i])) return false;
+        }
+        i += 1;
+    }
+    return i != 0 and (tones.len == 1 or i == tones.len);
+}
+
+// The group of skin tone variants that the fully-qualified emoji `cps` is
+// either in or the base (without skin tones) of.
+fn skinToneGroup(cps: []const u21) ?catalog.SkinToneGroup {
+    const entry = findEntry(cps) orelse return null;
+    if (entry.status != .fully_qualified) return null;
+    return catalog.skin_tone_groups[entry.skin_tone_group orelse return null];
+}
+
+// Iterates over the fully-qualified variants of an emoji that have skin
+// tones, in catalog order. See `skinToneVariants`.
+pub const SkinToneVariantIterator = struct {
+    variants: []const u16,
+    i: usize = 0,
+
+    pub fn next(self: *SkinToneVariantIterator) ?Info {
+        if (self.i == self.variants.len) return null;
+        defer self.i += 1;
+        return entryInfo(catalog.entries[self.variants[self.i]]);
+    }
+};
+
+// Returns an iterator over the RGI skin tone variants of the fully-qualified
+// emoji `cps`, which may itself have skin tones. For example, 👍 has five
+// variants, and 🧑‍🤝‍🧑 has 25, one for each combination of the two people's
+// skin tones. The variants are precomputed along with the catalog, with
+// U+FE0F only dropped where a modifier takes its place (as in ☝️ and ☝🏻).
+// Emoji with skin tones but no RGI variant without them, such as 🫱🏻‍🫲🏿,
+// are variants of each other.
+pub fn skinToneVariants(cps: []const u21) SkinToneVariantIterator {
+    const group = skinToneGroup(cps) orelse return .{ .variants = &.{} };
+    return .{
+        .variants = catalog.skin_tone_variants[group.variants_offset..][0..group.variants_len],
+    };
+}
+
+// Returns the RGI variant of the fully-qualified emoji `cps` with the skin
+// tones `tones`, one for each emoji modifier base (such as for each person
+// in 🧑🏻‍🤝‍🧑🏿), or a single tone to apply to all of them. Existing skin
+// tones are replaced. Returns null if there's no such RGI emoji.
+pub fn replaceSkinTones(cps: []const u21, tones: []const SkinTone) ?Info {
+    if (tones.len == 0) return stripSkinTones(cps);
+
+    var it = skinToneVariants(cps);
+    while (it.next()) |variant| {
+        if (hasSkinTones(variant.code_points, tones)) return variant;
+    }
+    return null;
+}
+
+// Returns the RGI variant of the fully-qualified emoji `cps` with `tone`
+// applied to every emoji modifier base, or null if there's no such RGI
+// emoji.
+pub fn applySkinTone(cps: []const u21, tone: SkinTone) ?Info {
+    return replaceSkinTones(cps, &.{tone});
+}
+
+// Returns the variant of the fully-qualified emoji `cps` without skin tones,
+// adding back U+FE0F where needed (☝🏻 becomes ☝️), or null if there's none.
+pub fn stripSkinTones(cps: []const u21) ?Info {
+    if (!hasSkinTone(cps)) {
+        const entry = findEntry(cps) orelse return null;
+        return if (entry.status == .fully_qualified) entryInfo(entry) else null;
+    }
+
+    const group = skinToneGroup(cps) orelse return null;
+    return entryInfo(catalog.entries[group.base orelse return null]);
+}
+
+test "info" {
+    const grinning = info(&.{0x1F600}).?;
+    try std.testing.expectEqualStrings("grinning face", grinning.name);
+    try std.testing.expectEqual(.fully_qualified, grinning.status);
+    try std.testing.expectEqual(Version{ .major = 1, .minor = 0 }, grinning.version);
+    try std.testing.expectEqualStrings("Smileys & Emotion", grinning.group);
+    try std.testing.expectEqualStrings("face-smiling", grinning.subgroup);
+
+    try std.testing.expectEqual(.fully_qualified, info(&.{ 0x263A, vs16 }).?.status);
+    try std.testing.expectEqual(.unqualified, info(&.{0x263A}).?.status);
+    try std.testing.expectEqual(.component, info(&.{0x1F3FD}).?.status);
+    try std.testing.expectEqual(.minimally_qualified, info(&.{ 0x1F3F3, zwj, 0x1F308 }).?.status);
+
+    const astronaut = info(&.{ 0x1F469, zwj, 0x1F680 }).?;
+    try std.testing.expectEqualStrings("woman astronaut", astronaut.name);
+    try std.testing.expectEqual(Version{ .major = 4, .minor = 0 }, astronaut.version);
+
+    try std.testing.expectEqual(null, info(&.{'a'}));
+    try std.testing.expectEqual(null, info(&.{ 0x1F600, zwj, 0x1F680 }));
+}
+
+test "catalogIterator" {
+    var it = catalogIterator();
+    const first = it.next().?;
+    try std.testing.expectEqualStrings("grinning face", first.name);
+
+    var count: usize = 1;
+    var prev_group = first.group;
+    var groups: usize = 1;
+    while (it.next()) |entry| {
+        count += 1;
+        if (!std.mem.eql(u8, entry.group, prev_group)) {
+            groups += 1;
+            prev_group = entry.group;
+        }
+    }
+    try std.testing.expect(count > 5000);
+    try std.testing.expectEqual(catalog.groups.len, groups);
+}
+
+test "search" {
+    var it = search("thumbs", .prefix);
+    try std.testing.expectEqualStrings("thumbs up", it.next().?.name);
+
+    it = search("Thumbs Up", .prefix);
+    try std.testing.expectEqualSlices(u21, &.{0x1F44D}, it.next().?.code_points);
+    try std.testing.expectEqualSlices(u21, &.{ 0x1F44D, 0x1F3FB }, it.next().?.code_points);
+
+    // "umbs" is in the middle of a word
+    it = search("umbs up", .prefix);
+    try std.testing.expectEqual(null, it.next());
+    it = search("umbs up", .substring);
+    try std.testing.expectEqualStrings("thumbs up", it.next().?.name);
+
+    // Only fully-qualified emoji are returned
+    it = search("smiling face", .prefix);
+    while (it.next()) |entry| {
+        try std.testing.expectEqual(.fully_qualified, entry.status);
+    }
+}
+
+test "shortcode" {
+    try emoji.testFormat(":thumbs_up:", shortcode(&.{0x1F44D}).?);
+    try emoji.testFormat(":thumbs_up::skin-tone-4:", shortcode(&.{ 0x1F44D, 0x1F3FD }).?);
+    try emoji.testFormat(":index_pointing_up::skin-tone-2:", shortcode(&.{ 0x261D, 0x1F3FB }).?);
+    try emoji.testFormat(":woman_astronaut:", shortcode(&.{ 0x1F469, zwj, 0x1F680 }).?);
+    try emoji.testFormat(":woman_astronaut::skin-tone-4:", shortcode(&.{ 0x1F469, 0x1F3FD, zwj, 0x1F680 }).?);
+    try emoji.testFormat(
+        ":people_holding_hands::skin-tone-2::skin-tone-6:",
+        shortcode(&.{ 0x1F9D1, 0x1F3FB, zwj, 0x1F91D, zwj, 0x1F9D1, 0x1F3FF }).?,
+    );
+    try emoji.testFormat(
+        ":people_holding_hands::skin-tone-6:",
+        shortcode(&.{ 0x1F9D1, 0x1F3FF, zwj, 0x1F91D, zwj, 0x1F9D1, 0x1F3FF }).?,
+    );
+
+    // 🫱🏻‍🫲🏿 has no variant without skin tones, so it uses its own name
+    try emoji.testFormat(
+        ":handshake_light_skin_tone_dark_skin_tone:",
+        shortcode(&.{ 0x1FAF1, 0x1F3FB, zwj, 0x1FAF2, 0x1F3FF }).?,
+    );
+    try emoji.testFormat(":keycap_hash:", shortcode(&.{ '#', vs16, combining_enclosing_keycap }).?);
+    try emoji.testFormat(":smiling_face:", shortcode(&.{ 0x263A, vs16 }).?);
+    try std.testing.expectEqual(null, shortcode(&.{0x263A}));
+    try std.testing.expectEqual(null, shortcode(&.{'a'}));
+}
+
+test "fromShortcode" {
+    try std.testing.expectEqualSlices(u21, &.{0x1F44D}, fromShortcode("thumbs_up").?.code_points);
+    try std.testing.expectEqualSlices(u21, &.{ 0x1F44D, 0x1F3FD }, fromShortcode("thumbs_up::skin-tone-4").?.code_points);
+    try std.testing.expectEqualSlices(u21, &.{ 0x261D, 0x1F3FB }, fromShortcode("index_pointing_up::skin-tone-2").?.code_points);
+    try std.testing.expectEqualSlices(u21, &.{ 0x263A, vs16 }, fromShortcode("smiling_face").?.code_points);
+    try std.testing.expectEqual(null, fromShortcode("thumbs_up::skin-tone-7"));
+    try std.testing.expectEqual(null, fromShortcode("grinning_face::skin-tone-2"));
+    try std.testing.expectEqual(null, fromShortcode("not_an_emoji"));
+    try std.testing.expectEqualSlices(
+        u21,
+        &.{ 0x1F469, 0x1F3FD, zwj, 0x1F680 },
+        fromShortcode("woman_astronaut::skin-tone-4").?.code_points,
+    );
+    try std.testing.expectEqualSlices(
+        u21,
+        &.{ 0x1F9D1, 0x1F3FB, zwj, 0x1F91D, zwj, 0x1F9D1, 0x1F3FF },
+        fromShortcode("people_holding_hands::skin-tone-2::skin-tone-6").?.code_points,
+    );
+}
+
+test "toShortcodes and fromShortcodes" {
+    try emoji.testFormat("nice :thumbs_up::skin-tone-4: :)", toShortcodes("nice \u{1F44D}\u{1F3FD} :)"));
+    try emoji.testFormat("nice \u{1F44D}\u{1F3FD} :)", fromShortcodes("nice :thumbs_up::skin-tone-4: :)"));
+    try emoji.testFormat("a:b:\u{1F44D}", fromShortcodes("a:b::thumbs_up:"));
+    try emoji.testFormat(":nope: \u{1F469}\u{200D}\u{1F680}", fromShortcodes(":nope: :woman_astronaut:"));
+    try emoji.testFormat("\u{1F469}\u{1F3FD}\u{200D}\u{1F680}", fromShortcodes(":woman_astronaut::skin-tone-4:"));
+    try emoji.testFormat(
+        "\u{1F9D1}\u{1F3FB}\u{200D}\u{1F91D}\u{200D}\u{1F9D1}\u{1F3FF}!",
+        fromShortcodes(":people_holding_hands::skin-tone-2::skin-tone-6:!"),
+    );
+    try emoji.testFormat("\u{263A} stays", toShortcodes("\u{263A} stays"));
+}
+
+test "stripSkinTones" {
+    try std.testing.expectEqualSlices(u21, &.{0x1F44D}, stripSkinTones(&.{ 0x1F44D, 0x1F3FD }).?.code_points);
+    try std.testing.expectEqualSlices(u21, &.{ 0x261D, vs16 }, stripSkinTones(&.{ 0x261D, 0x1F3FB }).?.code_points);
+    try std.testing.expectEqualSlices(
+        u21,
+        &.{ 0x1F9D1, zwj, 0x1F91D, zwj, 0x1F9D1 },
+        stripSkinTones(&.{ 0x1F9D1, 0x1F3FB, zwj, 0x1F91D, zwj, 0x1F9D1, 0x1F3FF }).?.code_points,
+    );
+    try std.testing.expectEqualSlices(u21, &.{0x1F600}, stripSkinTones(&.{0x1F600}).?.code_points);
+    try std.testing.expectEqual(null, stripSkinTones(&.{'a'}));
+
+    // 🏌🏻‍♂️ keeps the U+FE0F after ♂
+    try std.testing.expectEqualSlices(
+        u21,
+        &.{ 0x1F3CC, vs16, zwj, 0x2642, vs16 },
+        stripSkinTones(&.{ 0x1F3CC, 0x1F3FB, zwj, 0x2642, vs16 }).?.code_points,
+    );
+
+    // 🫱🏻‍🫲🏿 has no variant without skin tones
+    try std.testing.expectEqual(null, stripSkinTones(&.{ 0x1FAF1, 0x1F3FB, zwj, 0x1FAF2, 0x1F3FF }));
+}
+
+test "applySkinTone" {
+    try std.testing.expectEqualSlices(u21, &.{ 0x1F44D, 0x1F3FD }, applySkinTone(&.{0x1F44D}, .medium).?.code_points);
+    try std.testing.expectEqualSlices(u21, &.{ 0x1F44D, 0x1F3FF }, applySkinTone(&.{ 0x1F44D, 0x1F3FB }, .dark).?.code_points);
+    try std.testing.expectEqualSlices(u21, &.{ 0x261D, 0x1F3FB }, applySkinTone(&.{ 0x261D, vs16 }, .light).?.code_points);
+    try std.testing.expectEqualSlices(u21, &.{ 0x1F469, 0x1F3FD, zwj, 0x1F680 }, applySkinTone(&.{ 0x1F469, zwj, 0x1F680 }, .medium).?.code_points);
+
+    // The handshake doesn't get a skin tone
+    try std.testing.expectEqualSlices(
+        u21,
+        &.{ 0x1F9D1, 0x1F3FF, zwj, 0x1F91D, zwj, 0x1F9D1, 0x1F3FF },
+        applySkinTone(&.{ 0x1F9D1, zwj, 0x1F91D, zwj, 0x1F9D1 }, .dark).?.code_points,
+    );
+
+    try std.testing.expectEqual(null, applySkinTone(&.{0x1F600}, .light));
+}
+
+test "replaceSkinTones" {
+    try std.testing.expectEqualSlices(
+        u21,
+        &.{ 0x1F9D1, 0x1F3FB, zwj, 0x1F91D, zwj, 0x1F9D1, 0x1F3FF },
+        replaceSkinTones(&.{ 0x1F9D1, zwj, 0x1F91D, zwj, 0x1F9D1 }, &.{ .light, .dark }).?.code_points,
+    );
+    try std.testing.expectEqualSlices(u21, &.{0x1F44D}, replaceSkinTones(&.{ 0x1F44D, 0x1F3FB }, &.{}).?.code_points);
+    try std.testing.expectEqual(null, replaceSkinTones(&.{0x1F44D}, &.{ .light, .dark }));
+}
+
+test "skinToneVariants" {
+    var it = skinToneVariants(&.{0x1F44D});
+    var count: usize = 0;
+    while (it.next()) |variant| : (count += 1) {
+        try std.testing.expectEqual(0x1F44D, variant.code_points[0]);
+        try std.testing.expectEqual(0x1F3FB + count, variant.code_points[1]);
+    }
+    try std.testing.expectEqual(5, count);
+
+    it = skinToneVariants(&.{ 0x1F9D1, zwj, 0x1F91D, zwj, 0x1F9D1 });
+    count = 0;
+    while (it.next()) |_| count += 1;
+    try std.testing.expectEqual(25, count);
+
+    it = skinToneVariants(&.{0x1F600});
+    try std.testing.expectEqual(null, it.next());
+
+    // A variant itself has the same variants
+    it = skinToneVariants(&.{ 0x1F44D, 0x1F3FD });
+    count = 0;
+    while (it.next()) |_| count += 1;
+    try std.testing.expectEqual(5, count);
+
+    // The mixed skin tone handshakes are variants of each other
+    it = skinToneVariants(&.{ 0x1FAF1, 0x1F3FB, zwj, 0x1FAF2, 0x1F3FF });
+    count = 0;
+    while (it.next()) |variant| : (count += 1) {
+        try std.testing.expectEqual(0x1FAF1, variant.code_points[0]);
+    }
+    try std.testing.expectEqual(20, count);
+
+    // Only fully-qualified emoji have variants
+    it = skinToneVariants(&.{0x261D});
+    try std.testing.expectEqual(null, it.next());
+}
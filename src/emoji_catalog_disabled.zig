@@ -0,0 +1,6 @@
+// Stands in for the generated emoji catalog when uucode is built without the
+// `emoji_catalog` option, so that using any of it is a clear error.
+
+comptime {
+    @compileError("The emoji catalog (`uucode.emoji.info` and friends) requires the `emoji_catalog` build option");
+}
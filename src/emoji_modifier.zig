@@ -0,0 +1,7 @@
+// Shared by `emoji.zig` and `generate_emoji.zig`, which can't import
+// `emoji.zig` since it depends on the generated tables.
+
+// Whether `cp` is one of the five skin tone modifiers, U+1F3FB..U+1F3FF
+pub fn isEmojiModifier(cp: u21) bool {
+    return cp >= 0x1F3FB and cp <= 0x1F3FF;
+}
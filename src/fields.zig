@@ -276,6 +276,38 @@ pub const fields: []const config.Field = &.{
     // the "emoji style" and "text style" lines separately.
     .{ .name = "is_emoji_vs_base", .type = bool },
 
+    // EmojiSequences
+    // The non-ZWJ RGI sequences from `emoji-sequences.txt`. Keycap and
+    // modifier sequences only need the base, since each listed base is RGI
+    // with `FE0F 20E3` or all five modifiers, respectively.
+    // `rgi_emoji_flag_pairs` is a bitset of the second regional indicator
+    // (bit 0 is U+1F1E6 'A') for each first regional indicator. The tag
+    // sequences are stored on U+1F3F4 WAVING BLACK FLAG the same way as
+    // `rgi_emoji_zwj_sequences`.
+    .{ .name = "rgi_emoji_basic", .type = types.RgiEmojiBasic },
+    .{ .name = "is_rgi_emoji_keycap_base", .type = bool },
+    .{ .name = "rgi_emoji_flag_pairs", .type = u26 },
+    .{ .name = "is_rgi_emoji_modifier_base", .type = bool },
+    .{
+        .name = "rgi_emoji_tag_sequences",
+        .type = []const u21,
+        .max_len = 21,
+        .max_offset = 21,
+        .embedded_len = 0,
+    },
+
+    // EmojiZwjSequences
+    // Each RGI ZWJ sequence from `emoji-zwj-sequences.txt` is stored on its
+    // first code point as the number of remaining code points followed by
+    // those code points (including the ZWJs).
+    .{
+        .name = "rgi_emoji_zwj_sequences",
+        .type = []const u21,
+        .max_len = 2233,
+        .max_offset = 8877,
+        .embedded_len = 0,
+    },
+
     // GraphemeBreak (derived)
     // This is derived from `original_grapheme_break`
     // (GraphemeBreakProperty.txt), `indic_conjunct_break`,
@@ -327,5 +359,12 @@ pub const fields: []const config.Field = &.{
 
     // Wcwidth (derived)
     .{ .name = "wcwidth_standalone", .type = u2 },
+    .{ .name = "wcwidth_standalone_cjk", .type = u2 },
     .{ .name = "wcwidth_zero_in_grapheme", .type = bool },
+    .{ .name = "compat_wcwidth_c", .type = u2 },
+    .{ .name = "compat_wcwidth_py", .type = u2 },
+    .{ .name = "compat_go_runewidth", .type = u2 },
+    .{ .name = "compat_unicode_width", .type = u2 },
+    .{ .name = "compat_uniseg", .type = u3 },
+    .{ .name = "compat_utf8proc", .type = u2 },
 };
@@ -0,0 +1,105 @@
+const std = @import("std");
+
+const grapheme = @import("grapheme.zig");
+const x = @import("x.zig");
+
+pub const Alignment = enum {
+    left,
+    right,
+    center,
+};
+
+pub const Overflow = union(enum) {
+    // Strings wider than the width are written in full.
+    overflow,
+
+    // Strings wider than the width are truncated (see `x.truncate`) with
+    // this ellipsis.
+    truncate: []const u8,
+};
+
+// A formatter that pads `str` to `width` columns as measured by
+// `grapheme.utf8Wcwidth`, rather than by byte count as `{s:<20}` does. Use
+// with "{f}". See `padded`.
+pub const Padded = struct {
+    str: []const u8,
+    width: usize,
+    alignment: Alignment,
+
+    // The UTF-8 encoded character to pad with, which should be a single
+    // column wide.
+    fill: []const u8,
+
+    overflow: Overflow = .overflow,
+
+    // Returns a copy that truncates strings wider than `width`, showing
+    // `ellipsis` at the end.
+    pub fn truncate(self: Padded, ellipsis: []const u8) Padded {
+        var result = self;
+        result.overflow = .{ .truncate = ellipsis };
+        return result;
+    }
+
+    pub fn format(self: Padded, writer: *std.Io.Writer) std.Io.Writer.Error!void {
+        const str_width = grapheme.utf8Wcwidth(self.str);
+
+        if (str_width > self.width) {
+            switch (self.overflow) {
+                .overflow => try writer.writeAll(self.str),
+                .truncate => |ellipsis| {
+                    const truncated = x.truncate(self.str, self.width, ellipsis, .end);
+                    try truncated.format(writer);
+                },
+            }
+            return;
+        }
+
+        const padding = self.width - str_width;
+        const before = switch (self.alignment) {
+            .left => 0,
+            .right => padding,
+            .center => padding / 2,
+        };
+
+        try writer.splatBytesAll(self.fill, before);
+        try writer.writeAll(self.str);
+        try writer.splatBytesAll(self.fill, padding - before);
+    }
+};
+
+pub fn padded(str: []const u8, width: usize, alignment: Alignment, fill: []const u8) Padded {
+    return .{
+        .str = str,
+        .width = width,
+        .alignment = alignment,
+        .fill = fill,
+    };
+}
+
+fn testPadded(expected: []const u8, p: Padded) !void {
+    var buffer: [64]u8 = undefined;
+    const actual = try std.fmt.bufPrint(&buffer, "{f}", .{p});
+    try std.testing.expectEqualStrings(expected, actual);
+}
+
+test "padded" {
+    try testPadded("abc   ", padded("abc", 6, .left, " "));
+    try testPadded("   abc", padded("abc", 6, .right, " "));
+    try testPadded(" abc  ", padded("abc", 6, .center, " "));
+    try testPadded("abc", padded("abc", 3, .center, " "));
+    try testPadded("......", padded("", 6, .left, "."));
+    try testPadded("ab──", padded("ab", 4, .left, "─"));
+}
+
+test "padded by display width" {
+    try testPadded("日本  ", padded("日本", 6, .left, " "));
+    try testPadded("  😀", padded("😀", 4, .right, " "));
+    try testPadded("e\u{0301}  ", padded("e\u{0301}", 3, .left, " "));
+}
+
+test "padded overflow" {
+    try testPadded("日本語", padded("日本語", 4, .left, " "));
+    try testPadded("日 …", padded("日本語", 4, .left, " ").truncate("…"));
+    try testPadded("hel…", padded("hello", 4, .right, " ").truncate("…"));
+    try testPadded("  hi", padded("hi", 4, .right, " ").truncate("…"));
+}
@@ -0,0 +1,515 @@
+const std = @import("std");
+const types = @import("types.zig");
+const utf8 = @import("utf8.zig");
+const isEmojiModifier = @import("emoji_modifier.zig").isEmojiModifier;
+
+// Generates the emoji catalog data (see `src/emoji_catalog.zig`) from
+// `ucd/emoji/emoji-test.txt`. Unlike the tables built by `generate.zig`,
+// the catalog is keyed by emoji sequence rather than by code point, so it's
+// written out as flat arrays instead.
+
+const Entry = struct {
+    code_points_offset: usize,
+    code_points_len: usize,
+    name_offset: usize,
+    name_len: usize,
+    status: types.EmojiStatus,
+    version_major: u8,
+    version_minor: u8,
+    group: usize,
+    subgroup: usize,
+    shortcode_offset: usize = 0,
+    shortcode_len: usize = 0,
+    skin_tone_group: ?usize = null,
+};
+
+// The fully-qualified emoji with skin tones that are variants of the same
+// emoji without skin tones (`base`). For the few with no such emoji, such as
+// 🫱🏻‍🫲🏿, `base` is null and they're grouped by their code points with the
+// modifiers removed (`key`).
+const SkinToneGroup = struct {
+    base: ?usize,
+    key: []const u21,
+    variants: std.ArrayList(usize) = .empty,
+};
+
+const status_map = std.StaticStringMap(types.EmojiStatus).initComptime(.{
+    .{ "component", .component },
+    .{ "fully-qualified", .fully_qualified },
+    .{ "minimally-qualified", .minimally_qualified },
+    .{ "unqualified", .unqualified },
+});
+
+pub fn main(init: std.process.Init.Minimal) !void {
+    var arena = std.heap.ArenaAllocator.init(std.heap.page_allocator);
+    defer arena.deinit();
+    const allocator = arena.allocator();
+
+    var threaded = std.Io.Threaded.init(allocator, .{ .environ = init.environ });
+    defer threaded.deinit();
+    const io = threaded.io();
+
+    var args_iter = try init.args.iterateAllocator(allocator);
+    defer args_iter.deinit();
+    _ = args_iter.skip(); // Skip program name
+
+    const output_path = args_iter.next() orelse std.debug.panic("No output file arg!", .{});
+
+    const file_path = "ucd/emoji/emoji-test.txt";
+
+    const file = try std.Io.Dir.cwd().openFile(io, file_path, .{});
+    var read_buffer: [2048]u8 = undefined;
+    var file_reader = file.reader(io, &read_buffer);
+    const content = try file_reader.interface.allocRemaining(allocator, .unlimited);
+    file.close(io);
+
+    var entries: std.ArrayList(Entry) = .empty;
+    var code_points: std.ArrayList(u21) = .empty;
+    var names: std.ArrayList(u8) = .empty;
+    var groups: std.ArrayList([]const u8) = .empty;
+    var subgroups: std.ArrayList([]const u8) = .empty;
+
+    var lines = std.mem.splitScalar(u8, content, '\n');
+    while (lines.next()) |line| {
+        const trimmed = std.mem.trim(u8, line, " \t\r");
+        if (trimmed.len == 0) continue;
+
+        if (std.mem.startsWith(u8, trimmed, "# group:")) {
+            try groups.append(allocator, std.mem.trim(u8, trimmed["# group:".len..], " "));
+            continue;
+        } else if (std.mem.startsWith(u8, trimmed, "# subgroup:")) {
+            try subgroups.append(allocator, std.mem.trim(u8, trimmed["# subgroup:".len..], " "));
+            continue;
+        } else if (trimmed[0] == '#') {
+            continue;
+        }
+
+        // 1F600 ; fully-qualified # 😀 E1.0 grinning face
+        const semicolon = std.mem.indexOfScalar(u8, trimmed, ';').?;
+        const hash = std.mem.indexOfScalar(u8, trimmed, '#').?;
+        const cp_str = std.mem.trim(u8, trimmed[0..semicolon], " ");
+        const status_str = std.mem.trim(u8, trimmed[semicolon + 1 .. hash], " ");
+
+        const status = status_map.get(status_str) orelse
+            std.debug.panic("Unknown emoji-test.txt status: {s}", .{status_str});
+
+        const code_points_offset = code_points.items.len;
+        var cp_parts = std.mem.splitScalar(u8, cp_str, ' ');
+        while (cp_parts.next()) |part| {
+            if (part.len == 0) continue;
+            try code_points.append(allocator, try std.fmt.parseInt(u21, part, 16));
+        }
+
+        // The comment is the emoji itself, the version, and the name.
+        var comment = std.mem.splitScalar(u8, std.mem.trim(u8, trimmed[hash + 1 ..], " "), ' ');
+        _ = comment.next().?;
+        const version_str = comment.next().?;
+        std.debug.assert(version_str[0] == 'E');
+        const dot = std.mem.indexOfScalar(u8, version_str, '.').?;
+        const name = comment.rest();
+
+        try entries.append(allocator, .{
+            .code_points_offset = code_points_offset,
+            .code_points_len = code_points.items.len - code_points_offset,
+            .name_offset = names.items.len,
+            .name_len = name.len,
+            .status = status,
+            .version_major = try std.fmt.parseInt(u8, version_str[1..dot], 10),
+            .version_minor = try std.fmt.parseInt(u8, version_str[dot + 1 ..], 10),
+            .group = groups.items.len - 1,
+            .subgroup = subgroups.items.len - 1,
+        });
+        try names.appendSlice(allocator, name);
+    }
+
+    // Indexes of `entries` sorted by code points, for binary search.
+    const sorted = try allocator.alloc(usize, entries.items.len);
+    for (sorted, 0..) |*s, i| s.* = i;
+    std.mem.sort(usize, sorted, SortContext{
+        .entries = entries.items,
+        .code_points = code_points.items,
+    }, SortContext.lessThan);
+
+    const ctx: SortContext = .{
+        .entries = entries.items,
+        .code_points = code_points.items,
+        .names = names.items,
+        .sorted = sorted,
+    };
+
+    // Shortcodes for the fully-qualified emoji (see `Shortcode` in
+    // `src/emoji_catalog.zig`).
+    var shortcodes: std.ArrayList(u8) = .empty;
+    var shortcode_index: std.ArrayList(usize) = .empty;
+    for (entries.items, 0..) |*e, i| {
+        if (e.status != .fully_qualified) continue;
+        e.shortcode_offset = shortcodes.items.len;
+        try appendShortcode(allocator, &shortcodes, ctx, i);
+        e.shortcode_len = shortcodes.items.len - e.shortcode_offset;
+        try shortcode_index.append(allocator, i);
+    }
+
+    // Indexes of the fully-qualified `entries` sorted by shortcode.
+    std.mem.sort(usize, shortcode_index.items, ShortcodeSortContext{
+        .entries = entries.items,
+        .shortcodes = shortcodes.items,
+    }, ShortcodeSortContext.lessThan);
+
+    for (shortcode_index.items[1..], 1..) |i, n| {
+        const prev = shortcode_index.items[n - 1];
+        const code = shortcodeOf(entries.items[i], shortcodes.items);
+        if (std.mem.eql(u8, code, shortcodeOf(entries.items[prev], shortcodes.items))) {
+            std.debug.panic("Duplicate emoji shortcode: {s}", .{code});
+        }
+    }
+
+    // Skin tone variants, grouped so that the variants of an emoji can be
+    // found without searching the whole catalog.
+    var skin_tone_groups: std.ArrayList(SkinToneGroup) = .empty;
+    for (entries.items, 0..) |*e, i| {
+        if (e.status != .fully_qualified) continue;
+        const cps = ctx.codePoints(i);
+        for (cps) |cp| {
+            if (isEmojiModifier(cp)) break;
+        } else continue;
+
+        const base = ctx.skinToneBase(i);
+        const key = if (base) |b| ctx.codePoints(b) else try withoutModifiers(allocator, cps);
+
+        const group_index = for (skin_tone_groups.items, 0..) |g, gi| {
+            if (std.mem.eql(u21, g.key, key)) break gi;
+        } else blk: {
+            try skin_tone_groups.append(allocator, .{ .base = base, .key = key });
+            if (base) |b| entries.items[b].skin_tone_group = skin_tone_groups.items.len - 1;
+            break :blk skin_tone_groups.items.len - 1;
+        };
+
+        try skin_tone_groups.items[group_index].variants.append(allocator, i);
+        e.skin_tone_group = group_index;
+    }
+
+    var out_file = try std.Io.Dir.cwd().createFile(io, output_path, .{});
+    defer out_file.close(io);
+    var out_buffer: [4096]u8 = undefined;
+    var file_writer = out_file.writer(io, &out_buffer);
+    var writer = &file_writer.interface;
+
+    try writer.writeAll(
+        \\//! This file is auto-generated. Do not edit.
+        \\
+        \\const types = @import("types.zig");
+        \\
+        \\pub const Entry = struct {
+        \\    code_points_offset: u16,
+        \\    code_points_len: u8,
+        \\    name_offset: u32,
+        \\    name_len: u8,
+        \\    status: types.EmojiStatus,
+        \\    version_major: u8,
+        \\    version_minor: u8,
+        \\    group: u8,
+        \\    subgroup: u8,
+        \\    shortcode_offset: u32,
+        \\    shortcode_len: u8,
+        \\    skin_tone_group: ?u16,
+        \\};
+        \\
+        \\pub const SkinToneGroup = struct {
+        \\    base: ?u16,
+        \\    variants_offset: u16,
+        \\    variants_len: u8,
+        \\};
+        \\
+        \\pub const groups: []const []const u8 = &.{
+        \\
+    );
+
+    for (groups.items) |group| {
+        try writer.print("    \"{f}\",\n", .{std.zig.fmtString(group)});
+    }
+
+    try writer.writeAll(
+        \\};
+        \\
+        \\pub const subgroups: []const []const u8 = &.{
+        \\
+    );
+
+    for (subgroups.items) |subgroup| {
+        try writer.print("    \"{f}\",\n", .{std.zig.fmtString(subgroup)});
+    }
+
+    try writer.writeAll(
+        \\};
+        \\
+        \\pub const code_points: []const u21 = &.{
+    );
+
+    for (code_points.items) |cp| {
+        try writer.print("{},", .{cp});
+    }
+
+    try writer.print(
+        \\}};
+        \\
+        \\pub const names: []const u8 = "{f}";
+        \\
+        \\pub const shortcodes: []const u8 = "{f}";
+        \\
+        \\pub const entries: []const Entry = &.{{
+        \\
+    , .{ std.zig.fmtString(names.items), std.zig.fmtString(shortcodes.items) });
+
+    for (entries.items) |e| {
+        try writer.print(
+            "    .{{ .code_points_offset = {}, .code_points_len = {}, .name_offset = {}, .name_len = {}, .status = .{s}, .version_major = {}, .version_minor = {}, .group = {}, .subgroup = {}, .shortcode_offset = {}, .shortcode_len = {}, .skin_tone_group = {?} }},\n",
+            .{
+                e.code_points_offset,
+                e.code_points_len,
+                e.name_offset,
+                e.name_len,
+                @tagName(e.status),
+                e.version_major,
+                e.version_minor,
+                e.group,
+                e.subgroup,
+                e.shortcode_offset,
+                e.shortcode_len,
+                e.skin_tone_group,
+            },
+        );
+    }
+
+    try writer.writeAll(
+        \\};
+        \\
+        \\pub const sorted: []const u16 = &.{
+    );
+
+    for (sorted) |i| {
+        try writer.print("{},", .{i});
+    }
+
+    try writer.writeAll(
+        \\};
+        \\
+        \\pub const shortcode_index: []const u16 = &.{
+    );
+
+    for (shortcode_index.items) |i| {
+        try writer.print("{},", .{i});
+    }
+
+    try writer.writeAll(
+        \\};
+        \\
+        \\pub const skin_tone_groups: []const SkinToneGroup = &.{
+        \\
+    );
+
+    var variants_offset: usize = 0;
+    for (skin_tone_groups.items) |g| {
+        try writer.print(
+            "    .{{ .base = {?}, .variants_offset = {}, .variants_len = {} }},\n",
+            .{ g.base, variants_offset, g.variants.items.len },
+        );
+        variants_offset += g.variants.items.len;
+    }
+
+    try writer.writeAll(
+        \\};
+        \\
+        \\pub const skin_tone_variants: []const u16 = &.{
+    );
+
+    for (skin_tone_groups.items) |g| {
+        for (g.variants.items) |i| {
+            try writer.print("{},", .{i});
+        }
+    }
+
+    try writer.writeAll(
+        \\};
+        \\
+    );
+
+    try writer.flush();
+}
+
+const SortContext = struct {
+    entries: []const Entry,
+    code_points: []const u21,
+    names: []const u8 = "",
+    sorted: []const usize = &.{},
+
+    fn codePoints(self: SortContext, i: usize) []const u21 {
+        const e = self.entries[i];
+        return self.code_points[e.code_points_offset..][0..e.code_points_len];
+    }
+
+    fn name(self: SortContext, i: usize) []const u8 {
+        const e = self.entries[i];
+        return self.names[e.name_offset..][0..e.name_len];
+    }
+
+    fn lessThan(self: SortContext, a: usize, b: usize) bool {
+        return std.mem.order(u21, self.codePoints(a), self.codePoints(b)) == .lt;
+    }
+
+    fn compare(self: SortContext, cps: []const u21, i: usize) std.math.Order {
+        return std.mem.order(u21, cps, self.codePoints(i));
+    }
+
+    // Returns the index of the fully-qualified entry for `cps`, if any.
+    fn fullyQualified(self: SortContext, cps: []const u21) ?usize {
+        const Ctx = struct {
+            ctx: SortContext,
+            cps: []const u21,
+
+            fn compare(c: @This(), i: usize) std.math.Order {
+                return c.ctx.compare(c.cps, i);
+            }
+        };
+        const found = std.sort.binarySearch(usize, self.sorted, Ctx{ .ctx = self, .cps = cps }, Ctx.compare) orelse
+            return null;
+        const i = self.sorted[found];
+        return if (self.entries[i].status == .fully_qualified) i else null;
+    }
+
+    // Returns the index of the fully-qualified entry without skin tones that
+    // `i` is a skin tone variant of, if any. Each modifier is either dropped
+    // or replaced by U+FE0F, since U+FE0F is dropped when a modifier is
+    // added, as in ☝️ and ☝🏻.
+    fn skinToneBase(self: SortContext, i: usize) ?usize {
+        const cps = self.codePoints(i);
+        var modifiers: usize = 0;
+        for (cps) |cp| {
+            if (isEmojiModifier(cp)) modifiers += 1;
+        }
+        if (modifiers == 0) return null;
+
+        var buffer: [16]u21 = undefined;
+        for (0..@as(usize, 1) << @intCast(modifiers)) |replace| {
+            var len: usize = 0;
+            var m: usize = 0;
+            for (cps) |cp| {
+                if (isEmojiModifier(cp)) {
+                    if (replace & (@as(usize, 1) << @intCast(m)) != 0) {
+                        buffer[len] = 0xFE0F;
+                        len += 1;
+                    }
+                    m += 1;
+                } else {
+                    buffer[len] = cp;
+                    len += 1;
+                }
+            }
+            if (self.fullyQualified(buffer[0..len])) |base| return base;
+        }
+        return null;
+    }
+};
+
+fn withoutModifiers(allocator: std.mem.Allocator, cps: []const u21) ![]const u21 {
+    var result: std.ArrayList(u21) = .empty;
+    for (cps) |cp| {
+        if (!isEmojiModifier(cp)) try result.append(allocator, cp);
+    }
+    return result.items;
+}
+
+fn isShortcodeSeparator(cp: u21) bool {
+    if (cp < 0x80) return !std.ascii.isAlphanumeric(@intCast(cp));
+
+    // General Punctuation, such as the quotes in "Japanese “here” button"
+    return cp >= 0x2000 and cp <= 0x206F;
+}
+
+// Appends the shortcode derived from a CLDR short name: "thumbs up" is
+// `thumbs_up`, with runs of spaces and punctuation replaced by a single
+// underscore and ASCII letters lowercased.
+fn appendShortcodeName(
+    allocator: std.mem.Allocator,
+    shortcodes: *std.ArrayList(u8),
+    name: []const u8,
+) !void {
+    var it = utf8.Iterator.init(name);
+    var written = false;
+    var pending_separator = false;
+    while (it.next()) |cp| {
+        // Keep "keycap: #" and "keycap: *" distinct.
+        const word: ?[]const u8 = switch (cp) {
+            '#' => "hash",
+            '*' => "asterisk",
+            else => null,
+        };
+
+        if (word == null and isShortcodeSeparator(cp)) {
+            pending_separator = written;
+            continue;
+        }
+
+        if (pending_separator) {
+            try shortcodes.append(allocator, '_');
+            pending_separator = false;
+        }
+
+        if (word) |w| {
+            try shortcodes.appendSlice(allocator, w);
+        } else if (cp < 0x80) {
+            try shortcodes.append(allocator, std.ascii.toLower(@intCast(cp)));
+        } else {
+            var buffer: [4]u8 = undefined;
+            const len = std.unicode.utf8Encode(cp, &buffer) catch unreachable;
+            try shortcodes.appendSlice(allocator, buffer[0..len]);
+        }
+        written = true;
+    }
+}
+
+// Emoji with skin tones use the shortcode of the emoji without them,
+// followed by a Slack-style `::skin-tone-2` (U+1F3FB) through
+// `::skin-tone-6` (U+1F3FF) suffix, or one suffix per modifier if they
+// differ (as in 🧑🏻‍🤝‍🧑🏿). The few with no such emoji without skin tones,
+// such as 🫱🏻‍🫲🏿 (as 🫱‍🫲 isn't an emoji), use their own name instead.
+fn appendShortcode(
+    allocator: std.mem.Allocator,
+    shortcodes: *std.ArrayList(u8),
+    ctx: SortContext,
+    i: usize,
+) !void {
+    const base = ctx.skinToneBase(i) orelse
+        return appendShortcodeName(allocator, shortcodes, ctx.name(i));
+
+    try appendShortcodeName(allocator, shortcodes, ctx.name(base));
+
+    var modifiers: [4]u21 = undefined;
+    var len: usize = 0;
+    for (ctx.codePoints(i)) |cp| {
+        if (!isEmojiModifier(cp)) continue;
+        modifiers[len] = cp;
+        len += 1;
+    }
+
+    const all_same = std.mem.allEqual(u21, modifiers[0..len], modifiers[0]);
+    for (modifiers[0..if (all_same) 1 else len]) |modifier| {
+        try shortcodes.print(allocator, "::skin-tone-{d}", .{modifier - 0x1F3FB + 2});
+    }
+}
+
+fn shortcodeOf(e: Entry, shortcodes: []const u8) []const u8 {
+    return shortcodes[e.shortcode_offset..][0..e.shortcode_len];
+}
+
+const ShortcodeSortContext = struct {
+    entries: []const Entry,
+    shortcodes: []const u8,
+
+    fn lessThan(self: ShortcodeSortContext, a: usize, b: usize) bool {
+        return std.mem.order(
+            u8,
+            shortcodeOf(self.entries[a], self.shortcodes),
+            shortcodeOf(self.entries[b], self.shortcodes),
+        ) == .lt;
+    }
+};
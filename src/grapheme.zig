@@ -3,6 +3,7 @@ const std = @import("std");
 const types = @import("types.zig");
 const getpkg = @import("get.zig");
 const utf8 = @import("utf8.zig");
+const emoji = @import("emoji.zig");
 const inlineAssert = @import("config.zig").quirks.inlineAssert;
 const get = getpkg.get;
 const FieldEnum = getpkg.FieldEnum;
@@ -17,12 +18,70 @@ pub const Grapheme = struct {
     end: usize,
 };
 
+// A maximum length for grapheme clusters, for untrusted input where a single
+// cluster may be thousands of combining marks long ("Zalgo" text). See
+// `CustomLimitedIterator`. UAX #29 allows implementations to limit the length
+// of grapheme clusters, for instance following the Stream-Safe Text Format of
+// UAX #15 (see `utf8StripExcessNonStarters`).
+pub const Limit = union(enum) {
+    code_points: usize,
+
+    // In units of the `i` of the CodePointIterator, which are bytes for
+    // `utf8.Iterator`. This must be at least 4 to fit any code point.
+    bytes: usize,
+
+    pub fn max(self: Limit) usize {
+        return switch (self) {
+            inline else => |m| m,
+        };
+    }
+};
+
 pub fn CustomIterator(
     comptime CodePointIterator: type,
     comptime GB: type,
     comptime State: type,
     comptime grapheme_break_field: FieldEnum,
     comptime customIsBreak: fn (gb1: GB, gb2: GB, state: *State) bool,
+) type {
+    return CustomIteratorImpl(
+        CodePointIterator,
+        GB,
+        State,
+        grapheme_break_field,
+        customIsBreak,
+        false,
+    );
+}
+
+// The same as `CustomIterator`, but forcing a break before a code point that
+// would make the current grapheme cluster longer than the `Limit` passed to
+// `init`. This is kept separate so the bookkeeping isn't paid for by
+// iterators without a limit.
+pub fn CustomLimitedIterator(
+    comptime CodePointIterator: type,
+    comptime GB: type,
+    comptime State: type,
+    comptime grapheme_break_field: FieldEnum,
+    comptime customIsBreak: fn (gb1: GB, gb2: GB, state: *State) bool,
+) type {
+    return CustomIteratorImpl(
+        CodePointIterator,
+        GB,
+        State,
+        grapheme_break_field,
+        customIsBreak,
+        true,
+    );
+}
+
+fn CustomIteratorImpl(
+    comptime CodePointIterator: type,
+    comptime GB: type,
+    comptime State: type,
+    comptime grapheme_break_field: FieldEnum,
+    comptime customIsBreak: fn (gb1: GB, gb2: GB, state: *State) bool,
+    comptime limited: bool,
 ) type {
     return struct {
         // This "i" is part of the documented API of this iterator, pointing to
@@ -35,9 +94,43 @@ pub fn CustomIterator(
         next_cp: ?u21,
         next_gb: GB,
 
+        limit: if (limited) Limit else void,
+
+        // The length of the current grapheme cluster so far, in the units of
+        // `limit`.
+        cluster_len: if (limited) usize else void = if (limited) 0 else {},
+
+        // Whether the break after the last code point returned was forced by
+        // `limit`, meaning the grapheme cluster it ends was truncated. After
+        // `initResume`, this is whether the break at the resume point was.
+        truncated: if (limited) bool else void = if (limited) false else {},
+
         const Self = @This();
 
-        pub fn init(cp_it: CodePointIterator) Self {
+        pub const init = if (limited) initLimited else initUnlimited;
+
+        fn initUnlimited(cp_it: CodePointIterator) Self {
+            var next_cp_it = cp_it;
+            const i = next_cp_it.i;
+            const next_cp = next_cp_it.next();
+
+            return .{
+                .state = .default,
+                .i = i,
+                .next_cp_it = next_cp_it,
+                .next_cp = next_cp,
+                .next_gb = if (next_cp) |code_point|
+                    get(grapheme_break_field, code_point)
+                else
+                    .other,
+                .limit = {},
+            };
+        }
+
+        fn initLimited(cp_it: CodePointIterator, limit: Limit) Self {
+            inlineAssert(limit != .bytes or limit.bytes >= 4);
+            inlineAssert(limit.max() > 0);
+
             var next_cp_it = cp_it;
             const i = next_cp_it.i;
             const next_cp = next_cp_it.next();
@@ -51,6 +144,15 @@ pub fn CustomIterator(
                     get(grapheme_break_field, code_point)
                 else
                     .other,
+                .limit = limit,
+            };
+        }
+
+        // The length of the next code point in the units of `limit`.
+        fn nextLen(self: Self) usize {
+            return switch (self.limit) {
+                .code_points => 1,
+                .bytes => self.next_cp_it.i - self.i,
             };
         }
 
@@ -59,17 +161,33 @@ pub fn CustomIterator(
 
             const cp1 = self.next_cp.?;
             const gb1 = self.next_gb;
+            if (limited) {
+                self.truncated = false;
+                self.cluster_len += self.nextLen();
+            }
             self.i = self.next_cp_it.i;
             self.next_cp = self.next_cp_it.next();
 
             if (self.next_cp) |cp2| {
                 self.next_gb = get(grapheme_break_field, cp2);
-                const is_break = customIsBreak(gb1, self.next_gb, &self.state);
+                var is_break = customIsBreak(gb1, self.next_gb, &self.state);
+
+                if (limited) {
+                    if (!is_break and self.cluster_len + self.nextLen() > self.limit.max()) {
+                        is_break = true;
+                        self.truncated = true;
+                        self.state = .default;
+                    }
+                    if (is_break) self.cluster_len = 0;
+                }
+
                 return IteratorResult{
                     .code_point = cp1,
                     .is_break = is_break,
                 };
             } else {
+                // The length of the cluster is kept, since it may continue
+                // when resuming from a `snapshot`.
                 return IteratorResult{
                     .code_point = cp1,
                     .is_break = true,
@@ -93,6 +211,85 @@ pub fn CustomIterator(
             var it = self;
             return it.nextGrapheme();
         }
+
+        // A small plain struct of the state of an iterator, which can be
+        // stored and later used to resume iteration over a different backing
+        // slice (see `initResume`), e.g. when text is stored in pages and a
+        // grapheme cluster may cross from one page to the next.
+        pub const Snapshot = struct {
+            state: State,
+
+            // The grapheme break value of the next code point, or of the last
+            // code point if `at_end`.
+            gb: GB,
+
+            // The `i` of the iterator when the snapshot was taken.
+            i: usize,
+
+            // Whether the iterator had reached the end of its code points.
+            // The last code point is returned with `is_break = true`, but the
+            // actual break depends on the code point that follows, which is
+            // accounted for when resuming (see `isBreakBefore`). An iterator
+            // over no code points at all is treated as ending with `.other`.
+            at_end: bool,
+
+            limit: if (limited) Limit else void,
+
+            // The length of the current grapheme cluster so far, so that the
+            // limit still applies to a cluster crossing the resume point.
+            cluster_len: if (limited) usize else void,
+
+            // Returns whether there's a break between the last code point
+            // before a snapshot taken `at_end` and `cp`, the first code point
+            // iteration is resumed from. This doesn't account for `limit`,
+            // which is applied by `initResume` (see `truncated`).
+            pub fn isBreakBefore(self: Snapshot, cp: u21) bool {
+                inlineAssert(self.at_end);
+                var state = self.state;
+                return customIsBreak(self.gb, get(grapheme_break_field, cp), &state);
+            }
+        };
+
+        pub fn snapshot(self: Self) Snapshot {
+            return .{
+                .state = self.state,
+                .gb = self.next_gb,
+                .i = self.i,
+                .at_end = self.next_cp == null,
+                .limit = self.limit,
+                .cluster_len = self.cluster_len,
+            };
+        }
+
+        // Resumes iteration from `s`, with `cp_it` positioned at the same
+        // place in the text as `s.i`, or for a snapshot taken `at_end`, at
+        // the start of the text that follows.
+        pub fn initResume(cp_it: CodePointIterator, s: Snapshot) Self {
+            var self = if (limited) initLimited(cp_it, s.limit) else initUnlimited(cp_it);
+            self.state = s.state;
+            if (limited) self.cluster_len = s.cluster_len;
+
+            if (s.at_end) {
+                if (self.next_cp == null) {
+                    // Still at the end, so keep the last code point's value.
+                    self.next_gb = s.gb;
+                } else {
+                    const is_break = customIsBreak(s.gb, self.next_gb, &self.state);
+
+                    if (limited) {
+                        if (!is_break and self.cluster_len + self.nextLen() > self.limit.max()) {
+                            self.truncated = true;
+                            self.state = .default;
+                            self.cluster_len = 0;
+                        } else if (is_break) {
+                            self.cluster_len = 0;
+                        }
+                    }
+                }
+            }
+
+            return self;
+        }
     };
 }
 
@@ -110,6 +307,24 @@ pub fn utf8Iterator(bytes: []const u8) Iterator(utf8.Iterator) {
     return Iterator(utf8.Iterator).init(.init(bytes));
 }
 
+pub fn LimitedIterator(comptime CodePointIterator: type) type {
+    return CustomLimitedIterator(
+        CodePointIterator,
+        types.GraphemeBreak,
+        BreakState,
+        .grapheme_break,
+        precomputedGraphemeBreak,
+    );
+}
+
+pub fn utf8LimitedIterator(bytes: []const u8, limit: Limit) LimitedIterator(utf8.Iterator) {
+    return LimitedIterator(utf8.Iterator).init(.init(bytes), limit);
+}
+
+pub fn utf8SegmentedIterator(segments: []const []const u8) Iterator(utf8.SegmentedIterator) {
+    return Iterator(utf8.SegmentedIterator).init(.init(segments));
+}
+
 test "Iterator nextCodePoint/peekCodePoint" {
     const str = "👩🏽‍🚀🇨🇭";
     var it = Iterator(utf8.Iterator).init(.init(str));
@@ -157,6 +372,78 @@ test "Iterator nextCodePoint/peekCodePoint" {
     try std.testing.expect(it.nextCodePoint() == null);
 }
 
+test "utf8SegmentedIterator" {
+    // 👩🏽‍🚀日 split partway through code points, e.g. in a ring buffer
+    const segments = [_][]const u8{ "\u{1F469}\xf0\x9f\x8f", "\xbd\u{200D}", "\u{1F680}\xe6", "\x97\xa5" };
+    var it = utf8SegmentedIterator(&segments);
+
+    const g = it.peekGrapheme().?;
+    try std.testing.expectEqual(0, g.start);
+    try std.testing.expectEqual(15, g.end);
+    try std.testing.expectEqual(
+        utf8.SegmentedIterator.Position{ .segment = 2, .offset = 4 },
+        it.next_cp_it.positionOf(g.end),
+    );
+
+    try std.testing.expectEqual(2, wcwidthNext(&it));
+    try std.testing.expectEqual(15, it.i);
+    try std.testing.expectEqual(2, wcwidthRemaining(&it));
+    try std.testing.expect(it.nextGrapheme() == null);
+}
+
+test "Iterator snapshot/initResume across pages" {
+    const full = "ab👩🏽‍🚀c🇺🇸🇦🇹🇼";
+    const pages = [_][]const u8{ "ab\u{1F469}", "", "\u{1F3FD}\u{200D}\u{1F680}c\u{1F1FA}\u{1F1F8}\u{1F1E6}", "\u{1F1F9}\u{1F1FC}" };
+
+    var expected = utf8Iterator(full);
+    var it = utf8Iterator(pages[0]);
+    for (pages[1..]) |page| {
+        while (it.nextCodePoint()) |result| {
+            const e = expected.nextCodePoint().?;
+            try std.testing.expectEqual(e.code_point, result.code_point);
+            if (it.next_cp != null) {
+                try std.testing.expectEqual(e.is_break, result.is_break);
+            } else {
+                // The break after the last code point of a page isn't known
+                // until resuming on the next page.
+                const snapshot = it.snapshot();
+                try std.testing.expect(snapshot.at_end);
+                if (page.len > 0) {
+                    const next = utf8.Iterator.init(page).peek().?;
+                    try std.testing.expectEqual(e.is_break, snapshot.isBreakBefore(next));
+                }
+            }
+        }
+        it = .initResume(.init(page), it.snapshot());
+    }
+
+    while (it.nextCodePoint()) |result| {
+        try std.testing.expectEqual(expected.nextCodePoint().?, result);
+    }
+    try std.testing.expect(expected.nextCodePoint() == null);
+}
+
+test "Iterator snapshot/initResume mid-stream" {
+    const str = "a👩🏽‍🚀\u{0915}\u{094D}\u{0924}b";
+
+    var it = utf8Iterator(str);
+    _ = it.nextCodePoint(); // a
+    _ = it.nextCodePoint(); // 👩
+    const snapshot = it.snapshot();
+    try std.testing.expect(!snapshot.at_end);
+    try std.testing.expectEqual(.emoji_modifier, snapshot.gb);
+
+    // Resume over a copy of the rest of the text
+    var rest: [64]u8 = undefined;
+    @memcpy(rest[0 .. str.len - snapshot.i], str[snapshot.i..]);
+    var resumed: Iterator(utf8.Iterator) = .initResume(.init(rest[0 .. str.len - snapshot.i]), snapshot);
+
+    while (it.nextCodePoint()) |result| {
+        try std.testing.expectEqual(result, resumed.nextCodePoint().?);
+    }
+    try std.testing.expect(resumed.nextCodePoint() == null);
+}
+
 test "utf8Iterator nextGrapheme/peekGrapheme" {
     const str = "👩🏽‍🚀🇨🇭👨🏻‍🍼";
     var it = utf8Iterator(str);
@@ -749,191 +1036,1499 @@ test "sequence of regional indicators" {
     try std.testing.expect(result.?.is_break); // break
 }
 
-// `wcwidth` (and `wcwidthRemaining`/`utf8Wcwidth`) are the full grapheme
-// cluster calculation of the expected width in cells of a monospaced font.
-// It is not part of the Unicode standard.
-//
-// See `src/components.zig` (Wcwidth) for the logic determining the width of
-// a single code point standing alone, as well as a number of notes describing
-// the choices the implementation makes.
-//
-// This implementation makes the following choices:
-//
-// * Only the context of the current grapheme cluster affects the width. The
-//   width of a string of grapheme clusters is the sum of the widths of the
-//   individual clusters.
-//
-// * Grapheme clusters with a single code point simply return
-//   `wcwidth_standalone`. See `src/components.zig` (Wcwidth) for all the
-//   considerations determining this value.
-//
-// * The general calculation of the width of a grapheme cluster is the sum of
-//   the widths of the individual code points, using
-//   `wcwidth_zero_in_grapheme` to treat a code point as width 0 in a multi-
-//   code-point grapheme cluster, otherwise using `wcwidth_standalone` for the
-//   widths of the code points.
-//
-//   Some alternative wcwidth implementations (see resources/wcwidth) only use
-//   the width of the first non-zero width code point, but this does not
-//   properly handle scripts such as Devanagari and Hangul, where multiple
-//   code points in the grapheme cluster may have non-zero width, and the
-//   resulting width is better represented by the sum.
-//
-// * Valid emoji sequences with VS16 (U+FEOF) return width 2, while
-//   valid text sequences with VS15 (U+FE0E) return width 1.
-//
-// * Emoji modifiers following an emoji modifier base force an emoji
-//   presentation, so the width will be 2.
-//
-// * Emoji ZWJ (zero-width joiner) sequences are a special case and the width
-//   of the emoji code points following the ZWJ are not added to the sum.
-//
-// * Regional indicator sequences are given a width of 2.
-//
-// * In contrast to `resources/wcwidth/unicode_width.rs`, this implementation
-//   does not include a large number of exceptions, in order to keep the
-//   complexity down.
-//
-//   While the Unicode General Punctuation doc
-//   (https://www.unicode.org/charts/PDF/Unicode-17.0/U170-2000.pdf) notes
-//   that U+2018, U+2019, U+201C and U+201D followed by U+FE02 (VS-2) should
-//   be fullwidth (width 2), we treat them as width 1 for simplicity.
-//
-//   Rather than treat CJK contexts differently, we always choose East Asian
-//   Width (UAX #11) Ambiguous width (A) as width 1. See
-//   `src/components.zig` (Wcwidth) for more info.
+test "Iterator limit code points" {
+    const str = "e" ++ "\u{0301}" ** 5 ++ "x";
+    var it = utf8LimitedIterator(str, .{ .code_points = 3 });
 
-// This calculates the width of just a single grapheme, advancing the iterator.
-// See `wcwidth` for a version that doesn't advance the iterator (accepting a
-// constant iterator), `wcwidthRemaining` for a version that calculates the
-// width of the remaining graphemes in the iterator, and `utf8Wcwidth` for the
-// width of a string.
-pub fn wcwidthNext(it: anytype) usize {
-    inlineAssert(@typeInfo(@TypeOf(it)) == .pointer);
+    var g = it.nextGrapheme().?;
+    try std.testing.expectEqualStrings("e\u{0301}\u{0301}", str[g.start..g.end]);
+    try std.testing.expect(it.truncated);
 
-    const first = it.nextCodePoint() orelse return 0;
+    g = it.nextGrapheme().?;
+    try std.testing.expectEqualStrings("\u{0301}" ** 3, str[g.start..g.end]);
+    try std.testing.expect(!it.truncated);
 
-    var prev_cp: u21 = first.code_point;
-    const standalone = get(.wcwidth_standalone, prev_cp);
+    g = it.nextGrapheme().?;
+    try std.testing.expectEqualStrings("x", str[g.start..g.end]);
+    try std.testing.expect(it.nextGrapheme() == null);
+}
 
-    if (first.is_break) return standalone;
+test "Iterator limit bytes" {
+    const str = "e\u{0301}\u{0301}👩🏽‍🚀";
+    var it = utf8LimitedIterator(str, .{ .bytes = 8 });
 
-    var width: usize = if (get(.wcwidth_zero_in_grapheme, prev_cp))
-        0
-    else
-        standalone;
+    var g = it.nextGrapheme().?;
+    try std.testing.expectEqualStrings("e\u{0301}\u{0301}", str[g.start..g.end]);
+    try std.testing.expect(!it.truncated);
 
-    var prev_state: BreakState = it.state;
-    inlineAssert(it.peekCodePoint() != null);
+    try std.testing.expectEqual(2, wcwidthNext(&it)); // 👩🏽
+    try std.testing.expect(it.truncated);
 
-    code_points: while (it.nextCodePoint()) |result| {
-        switch (result.code_point) {
-            0xFE0F => {
-                if (get(.is_emoji_vs_base, prev_cp)) {
-                    width = 2;
-                }
-            },
-            0xFE0E => {
-                if (get(.is_emoji_vs_base, prev_cp)) {
-                    width = 1;
-                }
-            },
-            0x200D => {
-                if (prev_state == .extended_pictographic and
-                    !result.is_break)
-                {
-                    const next = it.nextCodePoint() orelse unreachable;
-                    if (next.is_break) break;
-                    prev_cp = next.code_point;
-                    prev_state = it.state;
-                    continue :code_points;
-                }
-            },
-            0x1F3FB, 0x1F3FC, 0x1F3FD, 0x1F3FE, 0x1F3FF => {
-                width = 2;
-                inlineAssert(
-                    (comptime !getpkg.hasField("is_emoji_modifier_base")) or
-                        get(.is_emoji_modifier_base, prev_cp),
-                );
-            },
-            else => {
-                if (prev_state == .regional_indicator) {
-                    width = 2;
-                } else if (!get(.wcwidth_zero_in_grapheme, result.code_point)) {
-                    width += get(.wcwidth_standalone, result.code_point);
-                }
-            },
-        }
+    // The rest of the sequence is no longer joined to an emoji
+    g = it.nextGrapheme().?;
+    try std.testing.expectEqualStrings("\u{200D}", str[g.start..g.end]);
+    g = it.nextGrapheme().?;
+    try std.testing.expectEqualStrings("🚀", str[g.start..g.end]);
+    try std.testing.expect(it.nextGrapheme() == null);
+}
 
-        if (result.is_break) break;
+test "LimitedIterator snapshot/initResume" {
+    const pages = [_][]const u8{ "e\u{0301}", "\u{0301}\u{0301}x" };
+    var it = utf8LimitedIterator(pages[0], .{ .code_points = 3 });
+    while (it.nextCodePoint()) |_| {}
+    try std.testing.expectEqual(2, it.snapshot().cluster_len);
+
+    // The limit still applies to the cluster crossing the page boundary
+    it = .initResume(.init(pages[1]), it.snapshot());
+    try std.testing.expect(!it.truncated);
+    var result = it.nextCodePoint().?;
+    try std.testing.expect(result.is_break);
+    try std.testing.expect(it.truncated);
+    result = it.nextCodePoint().?;
+    try std.testing.expect(result.is_break);
+    try std.testing.expect(!it.truncated);
+
+    // A break is forced at the resume point itself
+    it = utf8LimitedIterator("e\u{0301}\u{0301}", .{ .code_points = 3 });
+    while (it.nextCodePoint()) |_| {}
+    it = .initResume(.init("\u{0301}x"), it.snapshot());
+    try std.testing.expect(it.truncated);
+    try std.testing.expectEqual(0, it.cluster_len);
+    result = it.nextCodePoint().?;
+    try std.testing.expectEqual(0x0301, result.code_point);
+    try std.testing.expect(result.is_break);
+    try std.testing.expect(!it.truncated);
+}
+
+// The maximum number of consecutive non-starters (code points with a
+// non-zero canonical combining class) in the Stream-Safe Text Format of
+// UAX #15 (Unicode Normalization Forms).
+pub const max_stream_safe_non_starters = 30;
+
+// Copies `s` into `dest` (which must be at least as long as `s`), stripping
+// any non-starters (code points with a non-zero `canonical_combining_class`)
+// beyond `max_non_starters` in a row, such as the excess combining marks of
+// "Zalgo" text. With `max_stream_safe_non_starters` this follows the
+// Stream-Safe Text Format, except that excess non-starters are removed
+// rather than separated by U+034F COMBINING GRAPHEME JOINER, and each code
+// point counts as one non-starter rather than by its decomposition.
+pub fn utf8StripExcessNonStarters(dest: []u8, s: []const u8, max_non_starters: usize) []u8 {
+    inlineAssert(dest.len >= s.len);
+
+    var it: utf8.Iterator = .init(s);
+    var len: usize = 0;
+    var non_starters: usize = 0;
+    var start: usize = 0;
+
+    while (it.next()) |cp| : (start = it.i) {
+        if (get(.canonical_combining_class, cp) == 0) {
+            non_starters = 0;
+        } else {
+            non_starters += 1;
+            if (non_starters > max_non_starters) continue;
+        }
 
-        prev_cp = result.code_point;
-        prev_state = it.state;
+        const bytes = s[start..it.i];
+        @memcpy(dest[len..][0..bytes.len], bytes);
+        len += bytes.len;
     }
 
-    return width;
+    return dest[0..len];
 }
 
-pub fn wcwidth(const_it: anytype) usize {
-    var it = const_it;
-    return wcwidthNext(&it);
-}
+test "utf8StripExcessNonStarters" {
+    var buf: [256]u8 = undefined;
 
-pub fn wcwidthRemaining(it: anytype) usize {
-    var width: usize = 0;
-    while (it.next_cp != null) {
-        width += wcwidthNext(it);
-    }
-    return width;
-}
+    const zalgo = "Z" ++ "\u{0301}\u{0316}" ** 20 ++ "a\u{0301}";
+    try std.testing.expectEqualStrings(
+        "Z" ++ "\u{0301}\u{0316}" ** 15 ++ "a\u{0301}",
+        utf8StripExcessNonStarters(&buf, zalgo, max_stream_safe_non_starters),
+    );
 
-pub fn utf8Wcwidth(s: []const u8) usize {
-    var it = utf8Iterator(s);
-    return wcwidthRemaining(&it);
+    try std.testing.expectEqualStrings(
+        "e\u{0301}o\u{0302}",
+        utf8StripExcessNonStarters(&buf, "e\u{0301}\u{0302}o\u{0302}", 1),
+    );
+    try std.testing.expectEqualStrings("abc", utf8StripExcessNonStarters(&buf, "abc", 0));
 }
 
-test "wcwidthNext iterator state" {
-    const str = "A\u{0300}B";
-    var it = utf8Iterator(str);
-
-    const w1 = wcwidthNext(&it);
-    try std.testing.expectEqual(1, w1);
-    try std.testing.expectEqual(3, it.i);
+// A pair of grapheme break values `gb1` and `gb2` is "safe" if there's always
+// a break between them and the resulting state is the same no matter the
+// state before. Forward iteration can begin after `gb1` (with the resulting
+// state) without needing to know anything that came before, which is what
+// allows iterating in reverse, or starting from any offset: scan back to a
+// safe pair (or the start of the text) and segment forward from there. This
+// scan goes only as far back as needed, for instance to before a run of
+// regional indicators (to count their parity) or before an Extended
+// Pictographic + Extend* + ZWJ or Indic conjunct sequence.
+pub fn SafeBreakTable(comptime GB: type, comptime State: type) type {
+    const n_gb = @typeInfo(GB).@"enum".fields.len;
 
-    const w2 = wcwidthNext(&it);
-    try std.testing.expectEqual(1, w2);
-    try std.testing.expectEqual(4, it.i);
+    return struct {
+        data: [n_gb][n_gb]?State,
 
-    try std.testing.expect(it.peekCodePoint() == null);
+        pub fn get(self: @This(), gb1: GB, gb2: GB) ?State {
+            return self.data[@intFromEnum(gb1)][@intFromEnum(gb2)];
+        }
+    };
 }
 
-test "wcwidthRemaining" {
-    var it1 = utf8Iterator("A\u{0300}B");
-    try std.testing.expectEqual(2, wcwidthRemaining(&it1));
+pub fn buildSafeBreakTable(
+    comptime GB: type,
+    comptime State: type,
+    compute: fn (gb1: GB, gb2: GB, state: *State) bool,
+) SafeBreakTable(GB, State) {
+    @setEvalBranchQuota(100_000);
+    var table: SafeBreakTable(GB, State) = undefined;
 
-    var it2 = utf8Iterator("ABC");
-    try std.testing.expectEqual(3, wcwidthRemaining(&it2));
+    const gb_fields = @typeInfo(GB).@"enum".fields;
+    const state_fields = @typeInfo(State).@"enum".fields;
 
-    var it3 = utf8Iterator("😀AB");
-    try std.testing.expectEqual(4, wcwidthRemaining(&it3));
+    for (gb_fields) |gb1_field| {
+        for (gb_fields) |gb2_field| {
+            const gb1: GB = @enumFromInt(gb1_field.value);
+            const gb2: GB = @enumFromInt(gb2_field.value);
 
-    var it4 = utf8Iterator("");
-    try std.testing.expectEqual(0, wcwidthRemaining(&it4));
+            var safe_state: ?State = null;
+            for (state_fields) |state_field| {
+                var state: State = @enumFromInt(state_field.value);
+                const result = compute(gb1, gb2, &state);
+                if (!result or (safe_state != null and safe_state.? != state)) {
+                    safe_state = null;
+                    break;
+                }
+                safe_state = state;
+            }
 
-    var it5 = utf8Iterator("ABC");
-    _ = wcwidthNext(&it5);
-    try std.testing.expectEqual(2, wcwidthRemaining(&it5));
-}
+            table.data[gb1_field.value][gb2_field.value] = safe_state;
+        }
+    }
 
-test "utf8Wcwidth" {
-    try std.testing.expectEqual(2, utf8Wcwidth("A\u{0300}B"));
+    return table;
 }
 
-test "wcwidth{,Next,Remaining} README example" {
-    const str = "ò👨🏻‍❤️‍👨🏿_";
-    var it = utf8Iterator(str);
+// An iterator over grapheme clusters in reverse, from the end of the text (or
+// from any grapheme boundary). The CodePointIterator must have a `prev`
+// method in addition to `next` (see `utf8.Iterator` and
+// `code_point.Iterator`), and its `i` is where reverse iteration begins. The
+// boundaries are the same as the corresponding forward `CustomIterator`.
+pub fn CustomReverseIterator(
+    comptime CodePointIterator: type,
+    comptime GB: type,
+    comptime State: type,
+    comptime grapheme_break_field: FieldEnum,
+    comptime customIsBreak: fn (gb1: GB, gb2: GB, state: *State) bool,
+) type {
+    const Forward = CustomIterator(
+        CodePointIterator,
+        GB,
+        State,
+        grapheme_break_field,
+        customIsBreak,
+    );
 
-    try std.testing.expectEqual(1, wcwidth(it));
+    return struct {
+        // This "i" is part of the documented API of this iterator, pointing to
+        // the current location of the iterator in the underlying bytes (the
+        // `i` of the CodePointIterator). It should always be at a grapheme
+        // boundary.
+        i: usize,
+
+        cp_it: CodePointIterator,
+
+        // The start of the grapheme cluster ending at `i`, if known (if it's
+        // less than `i`), so `prevCodePoint` doesn't need to find it for
+        // every code point.
+        grapheme_start: usize,
+
+        const Self = @This();
+
+        pub fn init(cp_it: CodePointIterator) Self {
+            return .{
+                .i = cp_it.i,
+                .cp_it = cp_it,
+                .grapheme_start = cp_it.i,
+            };
+        }
+
+        // Returns the position of the nearest safe pair before `end` (see
+        // `SafeBreakTable`), along with the state after it, to begin forward
+        // iteration from.
+        fn safeStart(self: Self, end: usize) struct { i: usize, state: State } {
+            const table = comptime buildSafeBreakTable(GB, State, customIsBreak);
+
+            var cp_it = self.cp_it;
+            cp_it.i = end;
+            var start = end;
+            var next_gb: ?GB = null;
+
+            while (cp_it.prev()) |cp| {
+                const gb = get(grapheme_break_field, cp);
+                if (next_gb) |gb2| {
+                    if (table.get(gb, gb2)) |state| {
+                        return .{ .i = start, .state = state };
+                    }
+                }
+                start = cp_it.i;
+                next_gb = gb;
+            }
+
+            return .{ .i = start, .state = .default };
+        }
+
+        // Returns the start of the grapheme cluster ending at `end`.
+        fn graphemeStart(self: Self, end: usize) usize {
+            const safe = self.safeStart(end);
+            var cp_it = self.cp_it;
+            cp_it.i = safe.i;
+
+            var it = Forward.init(cp_it);
+            it.state = safe.state;
+
+            var start = safe.i;
+            while (it.i < end) {
+                const g = it.nextGrapheme() orelse break;
+                start = g.start;
+            }
+            return start;
+        }
+
+        // Returns the code point before `i`, moving `i` back. In contrast to
+        // forward iteration, `is_break` indicates whether there's a break
+        // _before_ this code point, i.e. it's the first code point of its
+        // grapheme cluster.
+        pub fn prevCodePoint(self: *Self) ?IteratorResult {
+            if (self.i == 0) return null;
+
+            if (self.grapheme_start >= self.i) {
+                self.grapheme_start = self.graphemeStart(self.i);
+            }
+
+            self.cp_it.i = self.i;
+            const cp = self.cp_it.prev() orelse return null;
+            self.i = self.cp_it.i;
+
+            return .{
+                .code_point = cp,
+                .is_break = self.i <= self.grapheme_start,
+            };
+        }
+
+        pub fn peekCodePoint(self: Self) ?IteratorResult {
+            var it = self;
+            return it.prevCodePoint();
+        }
+
+        pub fn prevGrapheme(self: *Self) ?Grapheme {
+            if (self.i == 0) return null;
+
+            const end = self.i;
+            const start = if (self.grapheme_start < end)
+                self.grapheme_start
+            else
+                self.graphemeStart(end);
+
+            self.i = start;
+            self.grapheme_start = start;
+            return .{ .start = start, .end = end };
+        }
+
+        pub fn peekGrapheme(self: Self) ?Grapheme {
+            var it = self;
+            return it.prevGrapheme();
+        }
+    };
+}
+
+pub fn ReverseIterator(comptime CodePointIterator: type) type {
+    return CustomReverseIterator(
+        CodePointIterator,
+        types.GraphemeBreak,
+        BreakState,
+        .grapheme_break,
+        precomputedGraphemeBreak,
+    );
+}
+
+pub fn utf8ReverseIterator(bytes: []const u8) ReverseIterator(utf8.Iterator) {
+    var cp_it: utf8.Iterator = .init(bytes);
+    cp_it.i = bytes.len;
+    return ReverseIterator(utf8.Iterator).init(cp_it);
+}
+
+test "ReverseIterator prevGrapheme/peekGrapheme" {
+    const str = "a👩🏽‍🚀🇨🇭🇺🇸\r\ne\u{0301}";
+    var it = utf8ReverseIterator(str);
+    try std.testing.expect(it.i == str.len);
+
+    var g = it.peekGrapheme();
+    try std.testing.expect(it.i == str.len);
+    try std.testing.expectEqualStrings("e\u{0301}", str[g.?.start..g.?.end]);
+
+    g = it.prevGrapheme();
+    try std.testing.expectEqualStrings("e\u{0301}", str[g.?.start..g.?.end]);
+    try std.testing.expect(it.i == g.?.start);
+
+    g = it.prevGrapheme();
+    try std.testing.expectEqualStrings("\r\n", str[g.?.start..g.?.end]);
+
+    g = it.prevGrapheme();
+    try std.testing.expectEqualStrings("🇺🇸", str[g.?.start..g.?.end]);
+
+    g = it.prevGrapheme();
+    try std.testing.expectEqualStrings("🇨🇭", str[g.?.start..g.?.end]);
+
+    g = it.prevGrapheme();
+    try std.testing.expectEqualStrings("👩🏽‍🚀", str[g.?.start..g.?.end]);
+
+    g = it.prevGrapheme();
+    try std.testing.expectEqualStrings("a", str[g.?.start..g.?.end]);
+    try std.testing.expect(it.i == 0);
+
+    try std.testing.expect(it.prevGrapheme() == null);
+    try std.testing.expect(it.peekGrapheme() == null);
+}
+
+test "ReverseIterator prevCodePoint/peekCodePoint" {
+    const str = "a👩🏽‍🚀🇨🇭";
+    var it = utf8ReverseIterator(str);
+
+    var result = it.peekCodePoint();
+    try std.testing.expect(it.i == str.len);
+    try std.testing.expect(result.?.code_point == 0x1F1ED); // H
+    try std.testing.expect(result.?.is_break == false);
+
+    result = it.prevCodePoint();
+    try std.testing.expect(result.?.code_point == 0x1F1ED); // H
+    try std.testing.expect(result.?.is_break == false);
+
+    result = it.prevCodePoint();
+    try std.testing.expect(result.?.code_point == 0x1F1E8); // C
+    try std.testing.expect(result.?.is_break == true);
+
+    result = it.prevCodePoint();
+    try std.testing.expect(result.?.code_point == 0x1F680); // 🚀
+    try std.testing.expect(result.?.is_break == false);
+
+    result = it.prevCodePoint();
+    try std.testing.expect(result.?.code_point == 0x200D); // ZWJ
+    try std.testing.expect(result.?.is_break == false);
+
+    result = it.prevCodePoint();
+    try std.testing.expect(result.?.code_point == 0x1F3FD); // 🏽
+    try std.testing.expect(result.?.is_break == false);
+
+    result = it.prevCodePoint();
+    try std.testing.expect(result.?.code_point == 0x1F469); // 👩
+    try std.testing.expect(result.?.is_break == true);
+    try std.testing.expect(it.i == 1);
+
+    result = it.prevCodePoint();
+    try std.testing.expect(result.?.code_point == 'a');
+    try std.testing.expect(result.?.is_break == true);
+
+    try std.testing.expect(it.prevCodePoint() == null);
+}
+
+test "ReverseIterator regional indicator parity" {
+    // 🇺🇸🇦🇹🇼, where the last regional indicator is on its own.
+    const str = "\u{1F1FA}\u{1F1F8}\u{1F1E6}\u{1F1F9}\u{1F1FC}";
+    var it = utf8ReverseIterator(str);
+
+    try std.testing.expectEqual(Grapheme{ .start = 16, .end = 20 }, it.prevGrapheme().?);
+    try std.testing.expectEqual(Grapheme{ .start = 8, .end = 16 }, it.prevGrapheme().?);
+    try std.testing.expectEqual(Grapheme{ .start = 0, .end = 8 }, it.prevGrapheme().?);
+    try std.testing.expect(it.prevGrapheme() == null);
+}
+
+test "ReverseIterator code points" {
+    const code_points = &[_]u21{ 'x', 0x1F469, 0x200D, 0x1F680, 0x0915, 0x094D, 0x0924 };
+    var cp_it: @import("code_point.zig").Iterator = .init(code_points);
+    cp_it.i = code_points.len;
+    var it = ReverseIterator(@import("code_point.zig").Iterator).init(cp_it);
+
+    try std.testing.expectEqual(Grapheme{ .start = 4, .end = 7 }, it.prevGrapheme().?);
+    try std.testing.expectEqual(Grapheme{ .start = 1, .end = 4 }, it.prevGrapheme().?);
+    try std.testing.expectEqual(Grapheme{ .start = 0, .end = 1 }, it.prevGrapheme().?);
+    try std.testing.expect(it.prevGrapheme() == null);
+}
+
+test "GraphemeBreakTest.txt - ReverseIterator" {
+    const comps = @import("config.zig").components;
+
+    const trim = comps.trim;
+    const parseCp = comps.parseCp;
+
+    const allocator = std.testing.allocator;
+    const file_path = "ucd/auxiliary/GraphemeBreakTest.txt";
+
+    const file = try std.Io.Dir.cwd().openFile(std.testing.io, file_path, .{});
+    defer file.close(std.testing.io);
+
+    var buf: [2048]u8 = undefined;
+    var file_reader = file.reader(std.testing.io, &buf);
+    const content = try file_reader.interface.allocRemaining(allocator, .unlimited);
+    defer allocator.free(content);
+
+    var lines = std.mem.splitScalar(u8, content, '\n');
+    var success = true;
+
+    var line_num: usize = 1;
+
+    while (lines.next()) |line| : (line_num += 1) {
+        const trimmed = trim(line);
+        if (trimmed.len == 0) continue;
+
+        var bytes: [256]u8 = undefined;
+        var len: usize = 0;
+        var expected_starts: [64]usize = undefined;
+        var n_expected: usize = 0;
+
+        var parts = std.mem.splitScalar(u8, trimmed, ' ');
+        try std.testing.expect(std.mem.eql(u8, parts.next().?, "÷"));
+
+        var is_break = true;
+        var gb1: ?types.GraphemeBreak = null;
+        while (parts.next()) |cp_str| {
+            const cp = try parseCp(cp_str);
+            const gb2 = get(.grapheme_break, cp);
+
+            // See the comment above `isExtend` and in `testGraphemeBreak`.
+            if (gb1 != null and gb2 == .emoji_modifier and gb1.? != .emoji_modifier_base) {
+                is_break = true;
+            }
+
+            if (is_break) {
+                expected_starts[n_expected] = len;
+                n_expected += 1;
+            }
+
+            len += try std.unicode.utf8Encode(cp, bytes[len..]);
+            is_break = std.mem.eql(u8, parts.next().?, "÷");
+            gb1 = gb2;
+        }
+
+        var it = utf8ReverseIterator(bytes[0..len]);
+        var end = len;
+        var n_actual: usize = 0;
+        while (it.prevGrapheme()) |g| : (n_actual += 1) {
+            const k = n_expected -| (n_actual + 1);
+            if (n_actual >= n_expected or g.start != expected_starts[k] or g.end != end) {
+                std.log.err("line={d} grapheme={d}: expected start={d}, actual start={d}", .{
+                    line_num,
+                    n_actual,
+                    expected_starts[k],
+                    g.start,
+                });
+                success = false;
+                break;
+            }
+            end = g.start;
+        } else {
+            if (n_actual != n_expected) {
+                std.log.err("line={d}: expected {d} graphemes, actual {d}", .{
+                    line_num,
+                    n_expected,
+                    n_actual,
+                });
+                success = false;
+            }
+        }
+    }
+
+    try std.testing.expect(success);
+}
+
+// Returns the grapheme cluster boundaries at or around `offset` in `bytes`,
+// scanning back only as far as needed (see `SafeBreakTable`) rather than
+// segmenting from the start of `bytes`. If `offset` is in the middle of a
+// code point it's treated as within that code point's grapheme cluster,
+// assuming valid UTF-8 around `offset`.
+fn utf8BoundariesAt(bytes: []const u8, offset: usize) struct { before: usize, after: usize } {
+    inlineAssert(offset <= bytes.len);
+
+    // Back up over continuation bytes (0b10xxxxxx) to the code point start.
+    var cp_start = offset;
+    while (cp_start > 0 and
+        cp_start < bytes.len and
+        offset - cp_start < 3 and
+        bytes[cp_start] & 0xC0 == 0x80)
+    {
+        cp_start -= 1;
+    }
+
+    var cp_it: utf8.Iterator = .init(bytes);
+    cp_it.i = cp_start;
+    const safe = ReverseIterator(utf8.Iterator).init(cp_it).safeStart(cp_start);
+
+    cp_it.i = safe.i;
+    var it = Iterator(utf8.Iterator).init(cp_it);
+    it.state = safe.state;
+
+    while (it.nextGrapheme()) |g| {
+        if (g.end > offset) {
+            if (g.start == offset) break;
+            return .{ .before = g.start, .after = g.end };
+        }
+    }
+
+    return .{ .before = offset, .after = offset };
+}
+
+// Returns whether there's a grapheme cluster boundary at the byte `offset` in
+// `bytes`, without segmenting `bytes` from the start. The start and end of
+// `bytes` are always boundaries.
+pub fn isBoundaryAt(bytes: []const u8, offset: usize) bool {
+    const b = utf8BoundariesAt(bytes, offset);
+    return b.before == offset;
+}
+
+// Returns the nearest grapheme cluster boundary at or before the byte
+// `offset` in `bytes`.
+pub fn boundaryBefore(bytes: []const u8, offset: usize) usize {
+    return utf8BoundariesAt(bytes, offset).before;
+}
+
+// Returns the nearest grapheme cluster boundary at or after the byte `offset`
+// in `bytes`.
+pub fn boundaryAfter(bytes: []const u8, offset: usize) usize {
+    return utf8BoundariesAt(bytes, offset).after;
+}
+
+test "isBoundaryAt/boundaryBefore/boundaryAfter" {
+    const str = "x👩‍🚀y";
+    try std.testing.expect(isBoundaryAt(str, 0));
+    try std.testing.expect(isBoundaryAt(str, 1));
+    try std.testing.expect(!isBoundaryAt(str, 3)); // within 👩
+    try std.testing.expect(!isBoundaryAt(str, 5)); // before ZWJ
+    try std.testing.expect(!isBoundaryAt(str, 8)); // before 🚀
+    try std.testing.expect(isBoundaryAt(str, 12));
+    try std.testing.expect(isBoundaryAt(str, str.len));
+
+    try std.testing.expectEqual(1, boundaryBefore(str, 5));
+    try std.testing.expectEqual(12, boundaryAfter(str, 5));
+    try std.testing.expectEqual(1, boundaryBefore(str, 3));
+    try std.testing.expectEqual(12, boundaryAfter(str, 10));
+    try std.testing.expectEqual(12, boundaryBefore(str, 12));
+    try std.testing.expectEqual(12, boundaryAfter(str, 12));
+    try std.testing.expectEqual(str.len, boundaryAfter(str, str.len));
+}
+
+test "isBoundaryAt regional indicators" {
+    // 🇺🇸🇦🇹🇼
+    const str = "\u{1F1FA}\u{1F1F8}\u{1F1E6}\u{1F1F9}\u{1F1FC}";
+    try std.testing.expect(isBoundaryAt(str, 8));
+    try std.testing.expect(isBoundaryAt(str, 16));
+    try std.testing.expect(!isBoundaryAt(str, 4));
+    try std.testing.expect(!isBoundaryAt(str, 12));
+    try std.testing.expect(!isBoundaryAt(str, 2));
+
+    try std.testing.expectEqual(8, boundaryBefore(str, 12));
+    try std.testing.expectEqual(16, boundaryAfter(str, 12));
+    try std.testing.expectEqual(16, boundaryBefore(str, 18));
+    try std.testing.expectEqual(20, boundaryAfter(str, 18));
+}
+
+test "isBoundaryAt matches forward iteration" {
+    const strs = [_][]const u8{
+        "a👩🏽‍🚀🇨🇭👨🏻‍🍼\r\n",
+        "e\u{0301}\u{0308}\u{1F1E6}\u{1F1F9}\u{1F1FC}x",
+        "\u{0915}\u{094D}\u{0924}\u{0915}\u{0301}\u{094D}\u{200D}\u{0924}",
+        "\u{1100}\u{1161}\u{11A8}\u{AC00}\u{11A8}한",
+        "🏳️‍🌈 🧑‍🤝‍🧑\u{0600}x",
+    };
+
+    for (strs) |str| {
+        var it = utf8Iterator(str);
+        var boundary: usize = 0;
+        var offset: usize = 0;
+        while (it.nextGrapheme()) |g| {
+            try std.testing.expectEqual(boundary, g.start);
+            while (offset < g.end) : (offset += 1) {
+                try std.testing.expectEqual(offset == g.start, isBoundaryAt(str, offset));
+                try std.testing.expectEqual(g.start, boundaryBefore(str, offset));
+                const after = if (offset == g.start) g.start else g.end;
+                try std.testing.expectEqual(after, boundaryAfter(str, offset));
+            }
+            boundary = g.end;
+        }
+        try std.testing.expect(isBoundaryAt(str, str.len));
+    }
+}
+
+// A push-style segmenter for UTF-8 that arrives in chunks (such as reads from
+// a PTY), where code points and grapheme clusters may span chunks. It holds
+// the partially decoded code point, the last code point (whose break isn't
+// known until the next code point arrives) and the break state between calls
+// to `feed`. The results are the same as `CustomIterator` over the
+// concatenated chunks: each code point is emitted once the next one is
+// known, with `is_break` indicating that the grapheme cluster ending with it
+// is complete.
+pub fn CustomSegmenter(
+    comptime GB: type,
+    comptime State: type,
+    comptime grapheme_break_field: FieldEnum,
+    comptime customIsBreak: fn (gb1: GB, gb2: GB, state: *State) bool,
+) type {
+    return struct {
+        decoder: utf8.Decoder = .{},
+        state: State = .default,
+
+        // The last code point pushed, which hasn't been emitted yet.
+        pending_cp: ?u21 = null,
+        pending_gb: GB = .other,
+
+        const Self = @This();
+
+        pub const FeedIterator = struct {
+            segmenter: *Self,
+            chunk: []const u8,
+            i: usize = 0,
+
+            pub fn next(self: *FeedIterator) ?IteratorResult {
+                while (self.i < self.chunk.len) {
+                    const byte = self.chunk[self.i];
+                    self.i += 1;
+                    const cp = self.segmenter.decoder.next(byte) orelse continue;
+                    if (self.segmenter.push(cp)) |result| return result;
+                }
+                return null;
+            }
+        };
+
+        // Returns an iterator over the code points that become known with
+        // this chunk. The iterator must be run to completion (until it
+        // returns null) before the next call to `feed` or `finish`.
+        pub fn feed(self: *Self, chunk: []const u8) FeedIterator {
+            return .{
+                .segmenter = self,
+                .chunk = chunk,
+            };
+        }
+
+        // Pushes an already decoded code point, returning the previous code
+        // point (if any) now that its break is known.
+        pub fn push(self: *Self, cp: u21) ?IteratorResult {
+            const gb = get(grapheme_break_field, cp);
+            defer {
+                self.pending_cp = cp;
+                self.pending_gb = gb;
+            }
+
+            const cp1 = self.pending_cp orelse return null;
+            return .{
+                .code_point = cp1,
+                .is_break = customIsBreak(self.pending_gb, gb, &self.state),
+            };
+        }
+
+        // Call at the end of the stream (repeatedly, until it returns null)
+        // to emit the remaining code points, the last of which is always a
+        // break. The segmenter is then ready for a new stream.
+        pub fn finish(self: *Self) ?IteratorResult {
+            if (self.decoder.finish()) |cp| {
+                if (self.push(cp)) |result| return result;
+            }
+
+            const cp = self.pending_cp orelse return null;
+            self.pending_cp = null;
+            self.pending_gb = .other;
+            self.state = .default;
+            return .{
+                .code_point = cp,
+                .is_break = true,
+            };
+        }
+    };
+}
+
+pub const Segmenter = CustomSegmenter(
+    types.GraphemeBreak,
+    BreakState,
+    .grapheme_break,
+    precomputedGraphemeBreak,
+);
+
+test "Segmenter across chunks" {
+    var segmenter: Segmenter = .{};
+
+    // 👩🏽‍🚀 split in the middle of code points
+    var feed = segmenter.feed("\u{1F469}\xf0\x9f");
+    try std.testing.expect(feed.next() == null);
+
+    feed = segmenter.feed("\x8f\xbd\u{200D}\xf0");
+    var result = feed.next();
+    try std.testing.expect(result.?.code_point == 0x1F469); // 👩
+    try std.testing.expect(result.?.is_break == false);
+    result = feed.next();
+    try std.testing.expect(result.?.code_point == 0x1F3FD); // 🏽
+    try std.testing.expect(result.?.is_break == false);
+    try std.testing.expect(feed.next() == null);
+
+    feed = segmenter.feed("\x9f\x9a\x80a");
+    result = feed.next();
+    try std.testing.expect(result.?.code_point == 0x200D); // ZWJ
+    try std.testing.expect(result.?.is_break == false);
+    result = feed.next();
+    try std.testing.expect(result.?.code_point == 0x1F680); // 🚀
+    try std.testing.expect(result.?.is_break == true);
+    try std.testing.expect(feed.next() == null);
+
+    result = segmenter.finish();
+    try std.testing.expect(result.?.code_point == 'a');
+    try std.testing.expect(result.?.is_break == true);
+    try std.testing.expect(segmenter.finish() == null);
+}
+
+test "Segmenter matches Iterator for every chunk size" {
+    const str = "a👩🏽‍🚀🇨🇭🇺🇸\r\ne\u{0301}\u{0915}\u{094D}\u{0924}\xe2\x82";
+
+    for (1..str.len + 1) |chunk_size| {
+        var it = utf8Iterator(str);
+        var segmenter: Segmenter = .{};
+
+        var start: usize = 0;
+        while (start < str.len) : (start += chunk_size) {
+            var feed = segmenter.feed(str[start..@min(start + chunk_size, str.len)]);
+            while (feed.next()) |result| {
+                try std.testing.expectEqual(it.nextCodePoint().?, result);
+            }
+        }
+        while (segmenter.finish()) |result| {
+            try std.testing.expectEqual(it.nextCodePoint().?, result);
+        }
+        try std.testing.expect(it.nextCodePoint() == null);
+    }
+}
+
+// `wcwidth` (and `wcwidthRemaining`/`utf8Wcwidth`) are the full grapheme
+// cluster calculation of the expected width in cells of a monospaced font.
+// It is not part of the Unicode standard.
+//
+// See `src/components.zig` (Wcwidth) for the logic determining the width of
+// a single code point standing alone, as well as a number of notes describing
+// the choices the implementation makes.
+//
+// This implementation makes the following choices:
+//
+// * Only the context of the current grapheme cluster affects the width. The
+//   width of a string of grapheme clusters is the sum of the widths of the
+//   individual clusters.
+//
+// * Grapheme clusters with a single code point simply return
+//   `wcwidth_standalone`. See `src/components.zig` (Wcwidth) for all the
+//   considerations determining this value.
+//
+// * The general calculation of the width of a grapheme cluster is the sum of
+//   the widths of the individual code points, using
+//   `wcwidth_zero_in_grapheme` to treat a code point as width 0 in a multi-
+//   code-point grapheme cluster, otherwise using `wcwidth_standalone` for the
+//   widths of the code points.
+//
+//   Some alternative wcwidth implementations (see resources/wcwidth) only use
+//   the width of the first non-zero width code point, but this does not
+//   properly handle scripts such as Devanagari and Hangul, where multiple
+//   code points in the grapheme cluster may have non-zero width, and the
+//   resulting width is better represented by the sum.
+//
+// * Valid emoji sequences with VS16 (U+FEOF) return width 2, while
+//   valid text sequences with VS15 (U+FE0E) return width 1.
+//
+// * Emoji modifiers following an emoji modifier base force an emoji
+//   presentation, so the width will be 2.
+//
+// * Emoji ZWJ (zero-width joiner) sequences are a special case and the width
+//   of the emoji code points following the ZWJ are not added to the sum.
+//
+// * Regional indicator sequences are given a width of 2.
+//
+// * In contrast to `resources/wcwidth/unicode_width.rs`, this implementation
+//   does not include a large number of exceptions, in order to keep the
+//   complexity down. Pass `.{ .unicode_width = true }` to the `*Options`
+//   variants to follow unicode-width's rules instead.
+//
+//   While the Unicode General Punctuation doc
+//   (https://www.unicode.org/charts/PDF/Unicode-17.0/U170-2000.pdf) notes
+//   that U+2018, U+2019, U+201C and U+201D followed by U+FE02 (VS-2) should
+//   be fullwidth (width 2), we treat them as width 1 for simplicity.
+//
+//   By default, East Asian Width (UAX #11) Ambiguous width (A) is treated as
+//   width 1. Pass `.{ .cjk = true }` to the `*Options` variants to treat
+//   ambiguous characters as width 2 (using `wcwidth_standalone_cjk`), as is
+//   expected in East Asian (CJK) contexts. See `src/components.zig` (Wcwidth)
+//   for more info.
+
+pub const WcwidthOptions = struct {
+    // Treat East Asian Width Ambiguous (A) characters as width 2, using the
+    // `wcwidth_standalone_cjk` field instead of `wcwidth_standalone`.
+    cjk: bool = false,
+
+    // Sum the widths of each code point in the grapheme cluster, the way
+    // terminals without grapheme cluster support (DEC mode 2027) do, using
+    // `compat_wcwidth_c` (glibc `wcwidth`) for the code point widths. The
+    // `cjk` option has no effect in legacy mode.
+    legacy: bool = false,
+
+    // Match the widths of `resources/wcwidth/unicode_width.rs` (the
+    // unicode-width Rust crate), using `compat_unicode_width` for the code
+    // point widths and implementing its rules for sequences, including
+    // "\r\n", VS-2 quotes, and script-specific ligatures such as Arabic
+    // lam-alef. Since some of these ligatures span multiple grapheme
+    // clusters, a single `wcwidthNextOptions` call may advance past more
+    // than one grapheme. The `cjk` option has no effect in this mode, and
+    // the Kirat Rai ligatures are not implemented.
+    unicode_width: bool = false,
+
+    // Only collapse emoji ZWJ sequences to a single width when the whole
+    // grapheme cluster is an RGI ZWJ sequence (see `emoji.rgiKind`), summing
+    // the width of each emoji otherwise, since fonts render unsupported
+    // sequences like 🦷‍🦴 as separate glyphs. Only fully-qualified sequences
+    // are RGI, so a minimally-qualified sequence (missing a U+FE0F) or one
+    // followed by marks in the same cluster is also summed. Requires the
+    // `rgi_emoji_zwj_sequences` field.
+    rgi_zwj: bool = false,
+};
+
+inline fn wcwidthStandalone(cp: u21, comptime options: WcwidthOptions) u2 {
+    return if (options.cjk)
+        get(.wcwidth_standalone_cjk, cp)
+    else
+        get(.wcwidth_standalone, cp);
+}
+
+// This calculates the width of just a single grapheme, advancing the iterator.
+// See `wcwidth` for a version that doesn't advance the iterator (accepting a
+// constant iterator), `wcwidthRemaining` for a version that calculates the
+// width of the remaining graphemes in the iterator, and `utf8Wcwidth` for the
+// width of a string.
+pub fn wcwidthNext(it: anytype) usize {
+    return wcwidthNextOptions(it, .{});
+}
+
+pub fn wcwidthNextOptions(it: anytype, comptime options: WcwidthOptions) usize {
+    inlineAssert(@typeInfo(@TypeOf(it)) == .pointer);
+
+    if (options.legacy) {
+        var width: usize = 0;
+        while (it.nextCodePoint()) |result| {
+            width += get(.compat_wcwidth_c, result.code_point);
+            if (result.is_break) break;
+        }
+        return width;
+    }
+
+    if (options.unicode_width) {
+        return wcwidthNextUnicodeWidth(it);
+    }
+
+    const first = it.nextCodePoint() orelse return 0;
+    const standalone = wcwidthStandalone(first.code_point, options);
+
+    if (first.is_break) return standalone;
+
+    var collapsed: ClusterWidth = .init(first.code_point, it.state, options);
+
+    // With `rgi_zwj`, the widths with and without collapsing ZWJ sequences
+    // are both calculated in the same pass, while collecting the code points
+    // to check whether the cluster is an RGI ZWJ sequence.
+    var summed = collapsed;
+
+    // The longest RGI ZWJ sequences, such as 🧑🏻‍❤️‍💋‍🧑🏼, are 10 code points.
+    var cps: [10]u21 = undefined;
+    cps[0] = first.code_point;
+    var len: usize = 1;
+
+    inlineAssert(it.peekCodePoint() != null);
+
+    while (it.nextCodePoint()) |result| {
+        collapsed.add(result.code_point, result.is_break, it.state, options, true);
+
+        if (options.rgi_zwj) {
+            summed.add(result.code_point, result.is_break, it.state, options, false);
+            if (len < cps.len) cps[len] = result.code_point;
+            len += 1;
+        }
+
+        if (result.is_break) break;
+    }
+
+    if (options.rgi_zwj and (len > cps.len or emoji.rgiKind(cps[0..len]) != .zwj)) {
+        return summed.width;
+    }
+
+    return collapsed.width;
+}
+
+// The width of a grapheme cluster so far, for `wcwidthNextOptions`, updated
+// one code point at a time with `add`.
+const ClusterWidth = struct {
+    width: usize,
+
+    // The width before the current emoji, when the emoji of a ZWJ sequence
+    // aren't collapsed.
+    emoji_start_width: usize = 0,
+
+    prev_cp: u21,
+    prev_state: BreakState,
+
+    // Whether the previous code point was a ZWJ joining the next emoji into
+    // a collapsed ZWJ sequence, so the next code point adds no width.
+    joined: bool = false,
+
+    fn init(first: u21, state: BreakState, comptime options: WcwidthOptions) ClusterWidth {
+        return .{
+            .width = if (get(.wcwidth_zero_in_grapheme, first))
+                0
+            else
+                wcwidthStandalone(first, options),
+            .prev_cp = first,
+            .prev_state = state,
+        };
+    }
+
+    fn add(
+        self: *ClusterWidth,
+        cp: u21,
+        is_break: bool,
+        state: BreakState,
+        comptime options: WcwidthOptions,
+        comptime collapse_zwj: bool,
+    ) void {
+        defer {
+            self.prev_cp = cp;
+            self.prev_state = state;
+        }
+
+        if (self.joined) {
+            self.joined = false;
+            return;
+        }
+
+        switch (cp) {
+            0xFE0F => {
+                if (get(.is_emoji_vs_base, self.prev_cp)) {
+                    self.width = self.emoji_start_width + 2;
+                }
+            },
+            0xFE0E => {
+                if (get(.is_emoji_vs_base, self.prev_cp)) {
+                    self.width = self.emoji_start_width + 1;
+                }
+            },
+            0x200D => {
+                if (self.prev_state == .extended_pictographic and !is_break) {
+                    if (collapse_zwj) {
+                        self.joined = true;
+                    } else {
+                        self.emoji_start_width = self.width;
+                    }
+                }
+            },
+            0x1F3FB, 0x1F3FC, 0x1F3FD, 0x1F3FE, 0x1F3FF => {
+                self.width = self.emoji_start_width + 2;
+                inlineAssert(
+                    (comptime !getpkg.hasField("is_emoji_modifier_base")) or
+                        get(.is_emoji_modifier_base, self.prev_cp),
+                );
+            },
+            else => {
+                if (self.prev_state == .regional_indicator) {
+                    self.width = 2;
+                } else if (!get(.wcwidth_zero_in_grapheme, cp)) {
+                    self.width += wcwidthStandalone(cp, options);
+                }
+            },
+        }
+    }
+};
+
+const UnicodeWidthGrapheme = struct {
+    width: usize,
+    first_cp: u21,
+
+    // The last two code points, for detecting ligatures with the following
+    // grapheme cluster.
+    last_cp: u21,
+    before_last_cp: ?u21,
+};
+
+fn unicodeWidthGrapheme(it: anytype) ?UnicodeWidthGrapheme {
+    const first = it.nextCodePoint() orelse return null;
+
+    var g: UnicodeWidthGrapheme = .{
+        .width = get(.compat_unicode_width, first.code_point),
+        .first_cp = first.code_point,
+        .last_cp = first.code_point,
+        .before_last_cp = null,
+    };
+
+    if (first.is_break) return g;
+
+    var prev_state: BreakState = it.state;
+
+    code_points: while (it.nextCodePoint()) |result| {
+        const cp = result.code_point;
+        switch (cp) {
+            0xFE0F => {
+                if (get(.is_emoji_vs_base, g.last_cp)) {
+                    g.width = 2;
+                }
+            },
+            0xFE0E => {
+                // unicode-width only narrows text presentation sequences of
+                // emoji presentation characters, keeping the width of those
+                // in the Enclosed Ideographic Supplement block, and of text
+                // default characters that are wide, such as U+3030.
+                if (get(.is_emoji_vs_base, g.last_cp) and
+                    get(.is_emoji_presentation, g.last_cp) and
+                    !(g.last_cp >= 0x1F200 and g.last_cp <= 0x1F2FF))
+                {
+                    g.width = 1;
+                }
+            },
+            0xFE00, 0xFE01, 0xFE02 => {
+                switch (g.last_cp) {
+                    0x2018, 0x2019, 0x201C, 0x201D => {
+                        if (g.before_last_cp == null) {
+                            g.width = if (cp == 0xFE01) 2 else 1;
+                        }
+                    },
+                    else => {},
+                }
+            },
+            0x200D => {
+                if (prev_state == .extended_pictographic and
+                    !result.is_break)
+                {
+                    const next = it.nextCodePoint() orelse unreachable;
+                    g.before_last_cp = cp;
+                    g.last_cp = next.code_point;
+                    if (next.is_break) break;
+                    prev_state = it.state;
+                    continue :code_points;
+                }
+            },
+            0x1F3FB, 0x1F3FC, 0x1F3FD, 0x1F3FE, 0x1F3FF => {
+                g.width = 2;
+            },
+            // "\r\n" has a width of 1 in total.
+            '\n' => {},
+            else => {
+                g.width += get(.compat_unicode_width, cp);
+            },
+        }
+
+        g.before_last_cp = g.last_cp;
+        g.last_cp = cp;
+
+        if (result.is_break) break;
+
+        prev_state = it.state;
+    }
+
+    return g;
+}
+
+fn isTifinaghConsonant(cp: u21) bool {
+    return (cp >= 0x2D31 and cp <= 0x2D65) or cp == 0x2D6F;
+}
+
+fn isKhmerCoengEligibleLetter(cp: u21) bool {
+    return switch (cp) {
+        0x1780...0x1782,
+        0x1784...0x1787,
+        0x1789...0x178C,
+        0x178E...0x1793,
+        0x1795...0x1798,
+        0x179B...0x179D,
+        0x17A0,
+        0x17A2,
+        0x17A7,
+        0x17AB...0x17AC,
+        0x17AF,
+        => true,
+        else => false,
+    };
+}
+
+// Returns how much to reduce the combined width by when the grapheme cluster
+// `g` forms a ligature with the following grapheme cluster starting with
+// `next`, or `null` if they don't form a ligature.
+fn unicodeWidthLigature(g: UnicodeWidthGrapheme, next: u21) ?usize {
+    // Lisu tone letters
+    if (g.last_cp >= 0xA4F8 and g.last_cp <= 0xA4FB and
+        next >= 0xA4FC and next <= 0xA4FD) return 1;
+
+    // Arabic lam-alef, with any transparent marks on the lam
+    if (get(.joining_group, g.first_cp) == .lam and
+        get(.joining_group, next) == .alef) return 1;
+
+    // Khmer coeng signs
+    if (g.last_cp == 0x17D2 and isKhmerCoengEligibleLetter(next)) return 1;
+
+    const before_last = g.before_last_cp orelse return null;
+
+    if (g.last_cp == 0x200D) {
+        // Hebrew alef-ZWJ-lamed
+        if (before_last == 0x05D0 and next == 0x05DC) return 1;
+
+        // Old Turkic orkhon ec-ZWJ-orkhon i
+        if (before_last == 0x10C32 and next == 0x10C03) return 1;
+
+        // Buginese <a, -i> ZWJ ya
+        if (g.first_cp == 0x1A15 and before_last == 0x1A17 and next == 0x1A10) return 1;
+    }
+
+    // Tifinagh bi-consonants
+    if (isTifinaghConsonant(before_last) and isTifinaghConsonant(next)) {
+        if (g.last_cp == 0x2D7F) return 2;
+        if (g.last_cp == 0x200D) return 1;
+    }
+
+    return null;
+}
+
+fn wcwidthNextUnicodeWidth(it: anytype) usize {
+    var g = unicodeWidthGrapheme(it) orelse return 0;
+    var width = g.width;
+
+    while (it.peekCodePoint()) |next| {
+        const reduce = unicodeWidthLigature(g, next.code_point) orelse break;
+        g = unicodeWidthGrapheme(it).?;
+        width = width + g.width - reduce;
+    }
+
+    return width;
+}
+
+pub fn wcwidth(const_it: anytype) usize {
+    return wcwidthOptions(const_it, .{});
+}
+
+pub fn wcwidthOptions(const_it: anytype, comptime options: WcwidthOptions) usize {
+    var it = const_it;
+    return wcwidthNextOptions(&it, options);
+}
+
+pub fn wcwidthRemaining(it: anytype) usize {
+    return wcwidthRemainingOptions(it, .{});
+}
+
+pub fn wcwidthRemainingOptions(it: anytype, comptime options: WcwidthOptions) usize {
+    var width: usize = 0;
+    while (it.next_cp != null) {
+        width += wcwidthNextOptions(it, options);
+    }
+    return width;
+}
+
+pub fn utf8Wcwidth(s: []const u8) usize {
+    return utf8WcwidthOptions(s, .{});
+}
+
+pub fn utf8WcwidthOptions(s: []const u8, comptime options: WcwidthOptions) usize {
+    var it = utf8Iterator(s);
+    return wcwidthRemainingOptions(&it, options);
+}
+
+// Whether the width of the next grapheme differs between terminals that
+// support grapheme clusters (DEC mode 2027) and legacy terminals that sum the
+// width of each code point, meaning the cursor position is at risk of getting
+// out of sync if it's unknown which of the two the terminal does. This
+// doesn't advance the iterator.
+pub fn wcwidthLegacyDiffers(const_it: anytype, comptime options: WcwidthOptions) bool {
+    const grapheme_options = comptime blk: {
+        var o = options;
+        o.legacy = false;
+        break :blk o;
+    };
+    const legacy_options = comptime blk: {
+        var o = options;
+        o.legacy = true;
+        break :blk o;
+    };
+    return wcwidthOptions(const_it, grapheme_options) !=
+        wcwidthOptions(const_it, legacy_options);
+}
+
+pub const Presentation = enum {
+    text,
+    emoji,
+};
+
+// Properties of a grapheme cluster, aggregated in a single pass by
+// `nextGraphemeInfo`.
+pub const GraphemeInfo = struct {
+    start: usize,
+    end: usize,
+
+    // The number of code points in the grapheme cluster.
+    code_points: usize,
+
+    // The width as calculated by `wcwidthNext`.
+    width: usize,
+
+    // Emoji presentation for flags, keycaps, ZWJ sequences, modifier
+    // sequences and sequences with VS16 (U+FE0F), otherwise text presentation
+    // for sequences with VS15 (U+FE0E), and otherwise the default
+    // presentation of `base` (`is_emoji_presentation`).
+    presentation: Presentation,
+
+    // Regional indicator pairs (e.g. 🇨🇭) and tag sequences (e.g. 🏴󠁧󠁢󠁳󠁣󠁴󠁿).
+    is_flag: bool,
+
+    // A keycap base (0-9, # or *) followed by U+20E3.
+    is_keycap: bool,
+
+    // Extended pictographics joined by ZWJ (U+200D).
+    is_zwj_sequence: bool,
+
+    // An emoji modifier base followed by an emoji modifier (skin tone).
+    is_modifier_sequence: bool,
+
+    // The first code point that isn't a Prepend character (or the first code
+    // point, if they all are).
+    base: u21,
+};
+
+// Wraps a grapheme iterator, recording the properties of the code points
+// passed through it, so `nextGraphemeInfo` can reuse the `wcwidthNext` logic
+// without iterating the grapheme cluster twice.
+fn GraphemeInfoIterator(comptime It: type) type {
+    return struct {
+        it: *It,
+
+        // Mirrors `it.state`, which `wcwidthNext` checks.
+        state: @FieldType(It, "state"),
+
+        info: GraphemeInfo,
+        prev_cp: u21 = 0,
+        prev_gb: types.GraphemeBreak = .other,
+        has_base: bool = false,
+        variation_selector: ?u21 = null,
+
+        const Self = @This();
+
+        pub fn nextCodePoint(self: *Self) ?IteratorResult {
+            const result = self.it.nextCodePoint() orelse return null;
+            self.state = self.it.state;
+            self.record(result.code_point);
+            return result;
+        }
+
+        pub fn peekCodePoint(self: Self) ?IteratorResult {
+            return self.it.peekCodePoint();
+        }
+
+        fn record(self: *Self, cp: u21) void {
+            const info = &self.info;
+            const gb = get(.grapheme_break, cp);
+            info.code_points += 1;
+
+            if (info.code_points == 1) info.base = cp;
+            if (!self.has_base and gb != .prepend) {
+                info.base = cp;
+                self.has_base = true;
+            }
+
+            switch (cp) {
+                0xFE0E, 0xFE0F => {
+                    if (info.code_points > 1 and self.prev_cp == info.base) {
+                        self.variation_selector = cp;
+                    }
+                },
+                0x20E3 => {
+                    switch (info.base) {
+                        '0'...'9', '#', '*' => info.is_keycap = true,
+                        else => {},
+                    }
+                },
+                0x1F3FB...0x1F3FF => {
+                    if (self.prev_gb == .emoji_modifier_base) {
+                        info.is_modifier_sequence = true;
+                    }
+                },
+                0xE007F => {
+                    // Cancel tag, ending a tag sequence
+                    if (info.base == 0x1F3F4 and info.code_points > 2) {
+                        info.is_flag = true;
+                    }
+                },
+                else => {
+                    if (gb == .regional_indicator and
+                        self.prev_gb == .regional_indicator and
+                        info.code_points == 2)
+                    {
+                        info.is_flag = true;
+                    } else if (self.prev_cp == 0x200D and
+                        isExtendedPictographic(gb) and
+                        info.code_points > 2)
+                    {
+                        info.is_zwj_sequence = true;
+                    }
+                },
+            }
+
+            self.prev_cp = cp;
+            self.prev_gb = gb;
+        }
+    };
+}
+
+// Returns the next grapheme cluster along with its properties (see
+// `GraphemeInfo`), computed in a single pass, advancing the iterator.
+pub fn nextGraphemeInfo(it: anytype) ?GraphemeInfo {
+    inlineAssert(@typeInfo(@TypeOf(it)) == .pointer);
+    if (it.next_cp == null) return null;
+
+    var info_it: GraphemeInfoIterator(@TypeOf(it.*)) = .{
+        .it = it,
+        .state = it.state,
+        .info = .{
+            .start = it.i,
+            .end = it.i,
+            .code_points = 0,
+            .width = 0,
+            .presentation = .text,
+            .is_flag = false,
+            .is_keycap = false,
+            .is_zwj_sequence = false,
+            .is_modifier_sequence = false,
+            .base = 0,
+        },
+    };
+
+    const width = wcwidthNext(&info_it);
+
+    var info = info_it.info;
+    info.end = it.i;
+    info.width = width;
+    info.presentation = if (info.is_flag or
+        info.is_keycap or
+        info.is_zwj_sequence or
+        info.is_modifier_sequence)
+        .emoji
+    else if (info_it.variation_selector) |vs|
+        (if (vs == 0xFE0F) .emoji else .text)
+    else if (get(.is_emoji_presentation, info.base))
+        .emoji
+    else
+        .text;
+
+    return info;
+}
+
+test "nextGraphemeInfo" {
+    var it = utf8Iterator("👩🏽‍🚀🇨🇭1\u{FE0F}\u{20E3}☺☺\u{FE0F}\u{23F0}\u{FE0E}e\u{0301}\u{0600}1");
+
+    var info = nextGraphemeInfo(&it).?;
+    try std.testing.expectEqual(0, info.start);
+    try std.testing.expectEqual(15, info.end);
+    try std.testing.expectEqual(4, info.code_points);
+    try std.testing.expectEqual(2, info.width);
+    try std.testing.expectEqual(.emoji, info.presentation);
+    try std.testing.expect(info.is_zwj_sequence);
+    try std.testing.expect(info.is_modifier_sequence);
+    try std.testing.expect(!info.is_flag);
+    try std.testing.expect(!info.is_keycap);
+    try std.testing.expectEqual(0x1F469, info.base);
+
+    info = nextGraphemeInfo(&it).?;
+    try std.testing.expectEqual(2, info.code_points);
+    try std.testing.expectEqual(2, info.width);
+    try std.testing.expect(info.is_flag);
+    try std.testing.expect(!info.is_zwj_sequence);
+    try std.testing.expectEqual(.emoji, info.presentation);
+    try std.testing.expectEqual(0x1F1E8, info.base);
+
+    info = nextGraphemeInfo(&it).?;
+    try std.testing.expectEqual(3, info.code_points);
+    try std.testing.expectEqual(2, info.width);
+    try std.testing.expect(info.is_keycap);
+    try std.testing.expectEqual(.emoji, info.presentation);
+    try std.testing.expectEqual('1', info.base);
+
+    info = nextGraphemeInfo(&it).?;
+    try std.testing.expectEqual(1, info.width);
+    try std.testing.expectEqual(.text, info.presentation);
+    try std.testing.expectEqual(0x263A, info.base);
+
+    info = nextGraphemeInfo(&it).?;
+    try std.testing.expectEqual(2, info.width);
+    try std.testing.expectEqual(.emoji, info.presentation);
+
+    info = nextGraphemeInfo(&it).?;
+    try std.testing.expectEqual(1, info.width);
+    try std.testing.expectEqual(.text, info.presentation);
+    try std.testing.expectEqual(0x23F0, info.base); // ⏰ has default emoji presentation
+
+    info = nextGraphemeInfo(&it).?;
+    try std.testing.expectEqual(2, info.code_points);
+    try std.testing.expectEqual(1, info.width);
+    try std.testing.expectEqual(.text, info.presentation);
+    try std.testing.expect(!info.is_modifier_sequence);
+    try std.testing.expectEqual('e', info.base);
+
+    info = nextGraphemeInfo(&it).?;
+    try std.testing.expectEqual(2, info.code_points);
+    try std.testing.expectEqual('1', info.base);
+
+    try std.testing.expect(nextGraphemeInfo(&it) == null);
+}
+
+test "nextGraphemeInfo tag sequence flag" {
+    // 🏴󠁧󠁢󠁥󠁮󠁧󠁿 (England)
+    var it = utf8Iterator("\u{1F3F4}\u{E0067}\u{E0062}\u{E0065}\u{E006E}\u{E0067}\u{E007F}");
+    const info = nextGraphemeInfo(&it).?;
+    try std.testing.expectEqual(7, info.code_points);
+    try std.testing.expect(info.is_flag);
+    try std.testing.expectEqual(.emoji, info.presentation);
+    try std.testing.expectEqual(2, info.width);
+    try std.testing.expect(nextGraphemeInfo(&it) == null);
+}
+
+test "wcwidthNext iterator state" {
+    const str = "A\u{0300}B";
+    var it = utf8Iterator(str);
+
+    const w1 = wcwidthNext(&it);
+    try std.testing.expectEqual(1, w1);
+    try std.testing.expectEqual(3, it.i);
+
+    const w2 = wcwidthNext(&it);
+    try std.testing.expectEqual(1, w2);
+    try std.testing.expectEqual(4, it.i);
+
+    try std.testing.expect(it.peekCodePoint() == null);
+}
+
+test "wcwidthRemaining" {
+    var it1 = utf8Iterator("A\u{0300}B");
+    try std.testing.expectEqual(2, wcwidthRemaining(&it1));
+
+    var it2 = utf8Iterator("ABC");
+    try std.testing.expectEqual(3, wcwidthRemaining(&it2));
+
+    var it3 = utf8Iterator("😀AB");
+    try std.testing.expectEqual(4, wcwidthRemaining(&it3));
+
+    var it4 = utf8Iterator("");
+    try std.testing.expectEqual(0, wcwidthRemaining(&it4));
+
+    var it5 = utf8Iterator("ABC");
+    _ = wcwidthNext(&it5);
+    try std.testing.expectEqual(2, wcwidthRemaining(&it5));
+}
+
+test "utf8Wcwidth" {
+    try std.testing.expectEqual(2, utf8Wcwidth("A\u{0300}B"));
+}
+
+test "wcwidth{,Next,Remaining} README example" {
+    const str = "ò👨🏻‍❤️‍👨🏿_";
+    var it = utf8Iterator(str);
+
+    try std.testing.expectEqual(1, wcwidth(it));
 
     try std.testing.expectEqual(1, wcwidthNext(&it));
     const grapheme_result = it.peekGrapheme();
@@ -990,6 +2585,141 @@ test "wcwidth ambiguous" {
     try std.testing.expectEqual(1, wcwidth(it));
 }
 
+test "wcwidth ambiguous cjk" {
+    const it1 = utf8Iterator("\u{00A1}");
+    try std.testing.expectEqual(2, wcwidthOptions(it1, .{ .cjk = true }));
+    const it2 = utf8Iterator("\u{2460}"); // ① CIRCLED DIGIT ONE
+    try std.testing.expectEqual(2, wcwidthOptions(it2, .{ .cjk = true }));
+    const it3 = utf8Iterator("\u{03B1}"); // α GREEK SMALL LETTER ALPHA
+    try std.testing.expectEqual(2, wcwidthOptions(it3, .{ .cjk = true }));
+    const it4 = utf8Iterator("A");
+    try std.testing.expectEqual(1, wcwidthOptions(it4, .{ .cjk = true }));
+}
+
+test "utf8WcwidthOptions cjk" {
+    // ○ WHITE CIRCLE, Д CYRILLIC CAPITAL LETTER DE
+    try std.testing.expectEqual(2, utf8Wcwidth("\u{25CB}\u{0414}"));
+    try std.testing.expectEqual(4, utf8WcwidthOptions("\u{25CB}\u{0414}", .{ .cjk = true }));
+    // Combining marks are still zero width within a grapheme cluster
+    try std.testing.expectEqual(2, utf8WcwidthOptions("\u{03B1}\u{0301}", .{ .cjk = true }));
+}
+
+test "wcwidth rgi_zwj" {
+    // 👩‍🚀 woman astronaut is an RGI ZWJ sequence
+    const astronaut = "\u{1F469}\u{200D}\u{1F680}";
+    try std.testing.expectEqual(2, utf8Wcwidth(astronaut));
+    try std.testing.expectEqual(2, utf8WcwidthOptions(astronaut, .{ .rgi_zwj = true }));
+
+    // 🦷‍🦴 tooth, bone is not
+    const tooth_bone = "\u{1F9B7}\u{200D}\u{1F9B4}";
+    try std.testing.expectEqual(2, utf8Wcwidth(tooth_bone));
+    try std.testing.expectEqual(4, utf8WcwidthOptions(tooth_bone, .{ .rgi_zwj = true }));
+
+    // Each emoji keeps its own presentation selector and modifier
+    try std.testing.expectEqual(4, utf8WcwidthOptions("\u{1F9B7}\u{200D}\u{2764}\u{FE0F}", .{ .rgi_zwj = true }));
+    try std.testing.expectEqual(3, utf8WcwidthOptions("\u{1F9B7}\u{200D}\u{2764}", .{ .rgi_zwj = true }));
+    try std.testing.expectEqual(4, utf8WcwidthOptions("\u{1F44D}\u{1F3FD}\u{200D}\u{1F9B4}", .{ .rgi_zwj = true }));
+
+    // 🏳️‍🌈 rainbow flag
+    try std.testing.expectEqual(2, utf8WcwidthOptions("\u{1F3F3}\u{FE0F}\u{200D}\u{1F308}", .{ .rgi_zwj = true }));
+
+    // Sequences that aren't fully-qualified aren't RGI, so they're summed
+    try std.testing.expectEqual(1, utf8Wcwidth("\u{1F3F3}\u{200D}\u{1F308}"));
+    try std.testing.expectEqual(3, utf8WcwidthOptions("\u{1F3F3}\u{200D}\u{1F308}", .{ .rgi_zwj = true }));
+    try std.testing.expectEqual(2, utf8Wcwidth(astronaut ++ "\u{0301}"));
+    try std.testing.expectEqual(4, utf8WcwidthOptions(astronaut ++ "\u{0301}", .{ .rgi_zwj = true }));
+
+    // Each cluster is measured on its own
+    try std.testing.expectEqual(6, utf8WcwidthOptions(astronaut ++ tooth_bone, .{ .rgi_zwj = true }));
+}
+
+test "wcwidth legacy" {
+    // 👩‍👩‍👧 family: woman, woman, girl
+    const family = "\u{1F469}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+    try std.testing.expectEqual(2, utf8Wcwidth(family));
+    try std.testing.expectEqual(6, utf8WcwidthOptions(family, .{ .legacy = true }));
+
+    var it = utf8Iterator("e\u{0301}\u{2764}\u{FE0F}");
+    try std.testing.expectEqual(1, wcwidthNextOptions(&it, .{ .legacy = true }));
+    try std.testing.expectEqual(3, it.i);
+    try std.testing.expectEqual(1, wcwidthNextOptions(&it, .{ .legacy = true }));
+    try std.testing.expect(it.peekCodePoint() == null);
+}
+
+test "wcwidthLegacyDiffers" {
+    try std.testing.expect(!wcwidthLegacyDiffers(utf8Iterator("A"), .{}));
+    try std.testing.expect(!wcwidthLegacyDiffers(utf8Iterator("e\u{0301}"), .{}));
+    try std.testing.expect(!wcwidthLegacyDiffers(utf8Iterator("\u{4E00}"), .{}));
+    // ❤️ red heart (text default + VS16)
+    try std.testing.expect(wcwidthLegacyDiffers(utf8Iterator("\u{2764}\u{FE0F}"), .{}));
+    // 👍🏽 thumbs up: medium skin tone
+    try std.testing.expect(wcwidthLegacyDiffers(utf8Iterator("\u{1F44D}\u{1F3FD}"), .{}));
+    // Only the next grapheme is considered
+    try std.testing.expect(!wcwidthLegacyDiffers(utf8Iterator("A\u{2764}\u{FE0F}"), .{}));
+}
+
+test "wcwidth unicode_width" {
+    const uw: WcwidthOptions = .{ .unicode_width = true };
+
+    try std.testing.expectEqual(23, utf8WcwidthOptions("Ｈｅｌｌｏ, ｗｏｒｌｄ!", uw));
+    try std.testing.expectEqual(4, utf8WcwidthOptions("“\u{18000}”", uw));
+
+    // "\r\n" and other control characters
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\r\n", uw));
+    try std.testing.expectEqual(3, utf8WcwidthOptions("a\r\nb", uw));
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\t", uw));
+
+    // VS-2 quotes
+    try std.testing.expectEqual(2, utf8WcwidthOptions("\u{2018}\u{FE01}", uw));
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{2019}\u{FE00}", uw));
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{201C}\u{FE02}", uw));
+
+    // Regional indicators
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{1F1E6}", uw));
+    try std.testing.expectEqual(2, utf8WcwidthOptions("\u{1F1E8}\u{1F1ED}", uw));
+
+    // Combining marks standing alone
+    try std.testing.expectEqual(0, utf8WcwidthOptions("\u{0300}", uw));
+
+    // Text presentation sequences are only narrowed for emoji presentation
+    // characters outside of the Enclosed Ideographic Supplement
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{231A}\u{FE0E}", uw));
+    try std.testing.expectEqual(2, utf8WcwidthOptions("\u{1F21A}\u{FE0E}", uw));
+    try std.testing.expectEqual(2, utf8WcwidthOptions("\u{3030}\u{FE0E}", uw));
+    try std.testing.expectEqual(2, utf8WcwidthOptions("\u{303D}\u{FE0E}", uw));
+    try std.testing.expectEqual(2, utf8WcwidthOptions("\u{3297}\u{FE0E}", uw));
+    try std.testing.expectEqual(2, utf8WcwidthOptions("\u{3299}\u{FE0E}", uw));
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{2764}\u{FE0E}", uw));
+}
+
+test "wcwidth unicode_width ligatures" {
+    const uw: WcwidthOptions = .{ .unicode_width = true };
+
+    // Arabic lam-alef
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{0644}\u{0627}", uw));
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{0644}\u{065F}\u{065E}\u{0623}", uw));
+    // Buginese
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{1A15}\u{1A17}\u{200D}\u{1A10}", uw));
+    // Hebrew alef-lamed
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{05D0}\u{200D}\u{05DC}", uw));
+    try std.testing.expectEqual(2, utf8WcwidthOptions("\u{05D0}\u{05DC}", uw));
+    // Khmer coeng
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{1780}\u{17D2}\u{1780}", uw));
+    // Lisu tone letters
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{A4F9}\u{A4FC}", uw));
+    // Old Turkic
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{10C32}\u{200D}\u{10C03}", uw));
+    // Tifinagh
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{2D4F}\u{2D7F}\u{2D3E}", uw));
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{2D4F}\u{200D}\u{2D3E}", uw));
+    try std.testing.expectEqual(1, utf8WcwidthOptions("\u{2D7F}", uw));
+
+    // A ligature is consumed by a single `wcwidthNextOptions` call
+    var it = utf8Iterator("\u{0644}\u{0627}a");
+    try std.testing.expectEqual(1, wcwidthNextOptions(&it, uw));
+    try std.testing.expectEqual(4, it.i);
+}
+
 test "wcwidth fullwidth" {
     const it = utf8Iterator("\u{3000}");
     try std.testing.expectEqual(2, wcwidth(it));
@@ -1126,6 +2856,188 @@ test "wcwidth prepend characters don't contribute to width in grapheme cluster"
     try std.testing.expectEqual(1, wcwidth(it));
 }
 
+// Width profiles that reproduce other wcwidth implementations (see
+// `resources/wcwidth`), for when the width must match what a host shell,
+// terminal multiplexer or editor assumes rather than what is displayed by a
+// grapheme-aware terminal. See `src/components.zig` (Wcwidth) for the
+// per-code-point rules of each profile.
+pub const CompatProfile = enum {
+    // `wcwidth.c` (Markus Kuhn, also followed by glibc and musl): the sum of
+    // the widths of each code point.
+    wcwidth_c,
+
+    // `wcwidth.py` (jquast/wcwidth): the sum of the widths of each code
+    // point, except that a ZWJ hides the following code point and VS16 widens
+    // a preceding narrow emoji.
+    wcwidth_py,
+
+    // go-runewidth: the width of the first non-zero width code point of each
+    // grapheme cluster.
+    go_runewidth,
+
+    // The unicode-width Rust crate, the same as the `unicode_width` option
+    // of `wcwidthNextOptions`.
+    unicode_width,
+
+    // uniseg (Go): the width of the first code point of each grapheme
+    // cluster, adjusted by VS15/VS16 for emoji, plus the widths of the rest
+    // of the code points unless it's a regional indicator or Hangul L jamo.
+    uniseg,
+
+    // utf8proc (and Julia's `textwidth`): the sum of the widths of each code
+    // point.
+    utf8proc,
+
+    // `resources/wcwidth/zg.zig` and `resources/wcwidth/ziglyph.zig` are
+    // out of scope, see `src/components.zig` (Wcwidth).
+};
+
+pub fn compatWidth(cp: u21, comptime profile: CompatProfile) u3 {
+    return switch (profile) {
+        .wcwidth_c => get(.compat_wcwidth_c, cp),
+        .wcwidth_py => get(.compat_wcwidth_py, cp),
+        .go_runewidth => get(.compat_go_runewidth, cp),
+        .unicode_width => get(.compat_unicode_width, cp),
+        .uniseg => get(.compat_uniseg, cp),
+        .utf8proc => get(.compat_utf8proc, cp),
+    };
+}
+
+pub fn utf8WcwidthCompat(s: []const u8, comptime profile: CompatProfile) usize {
+    var width: usize = 0;
+
+    switch (profile) {
+        .wcwidth_c => {
+            var it = utf8.Iterator.init(s);
+            while (it.next()) |cp| {
+                width += get(.compat_wcwidth_c, cp);
+            }
+        },
+        .wcwidth_py => {
+            var it = utf8.Iterator.init(s);
+            var last_measured: ?u21 = null;
+            while (it.next()) |cp| {
+                if (cp == 0x200D) {
+                    _ = it.next();
+                } else if (cp == 0xFE0F and last_measured != null) {
+                    const prev = last_measured.?;
+                    if (get(.is_emoji_vs_base, prev) and
+                        get(.compat_wcwidth_py, prev) == 1)
+                    {
+                        width += 1;
+                    }
+                    last_measured = null;
+                } else {
+                    const w = get(.compat_wcwidth_py, cp);
+                    if (w > 0) last_measured = cp;
+                    width += w;
+                }
+            }
+        },
+        .go_runewidth => {
+            var it = utf8Iterator(s);
+            var grapheme_width: u2 = 0;
+            while (it.nextCodePoint()) |result| {
+                if (grapheme_width == 0) {
+                    grapheme_width = get(.compat_go_runewidth, result.code_point);
+                }
+                if (result.is_break) {
+                    width += grapheme_width;
+                    grapheme_width = 0;
+                }
+            }
+        },
+        .unicode_width => {
+            width = utf8WcwidthOptions(s, .{ .unicode_width = true });
+        },
+        .uniseg => {
+            var it = utf8Iterator(s);
+            while (it.nextCodePoint()) |first| {
+                const first_gb = get(.grapheme_break, first.code_point);
+                var grapheme_width: usize = get(.compat_uniseg, first.code_point);
+                var result = first;
+                while (!result.is_break) {
+                    result = it.nextCodePoint().?;
+                    const cp = result.code_point;
+                    if (isExtendedPictographic(first_gb)) {
+                        if (cp == 0xFE0E) {
+                            grapheme_width = 1;
+                        } else if (cp == 0xFE0F) {
+                            grapheme_width = 2;
+                        }
+                    } else if (first_gb != .regional_indicator and first_gb != .l) {
+                        grapheme_width += get(.compat_uniseg, cp);
+                    }
+                }
+                width += grapheme_width;
+            }
+        },
+        .utf8proc => {
+            var it = utf8.Iterator.init(s);
+            while (it.next()) |cp| {
+                width += get(.compat_utf8proc, cp);
+            }
+        },
+    }
+
+    return width;
+}
+
+test "compatWidth" {
+    try std.testing.expectEqual(1, compatWidth(0x0903, .wcwidth_c)); // Mc
+    try std.testing.expectEqual(0, compatWidth(0x0903, .wcwidth_py));
+    try std.testing.expectEqual(0, compatWidth(0x0903, .go_runewidth));
+    try std.testing.expectEqual(1, compatWidth(0x00AD, .wcwidth_c));
+    try std.testing.expectEqual(1, compatWidth(0x00AD, .wcwidth_py));
+    try std.testing.expectEqual(0, compatWidth(0x00AD, .go_runewidth));
+    try std.testing.expectEqual(2, compatWidth(0x1F3FB, .wcwidth_c));
+    try std.testing.expectEqual(0, compatWidth(0x1F3FB, .wcwidth_py));
+    try std.testing.expectEqual(0, compatWidth(0x1F3FB, .uniseg));
+    try std.testing.expectEqual(2, compatWidth(0x1F3FB, .utf8proc));
+    try std.testing.expectEqual(0, compatWidth(0x0903, .utf8proc));
+    try std.testing.expectEqual(1, compatWidth(0x0903, .uniseg)); // SpacingMark
+    try std.testing.expectEqual(4, compatWidth(0x2E3B, .uniseg)); // THREE-EM DASH
+    try std.testing.expectEqual(1, compatWidth(0x2764, .uniseg)); // ❤ (text default)
+    try std.testing.expectEqual(0, compatWidth(0x00AD, .unicode_width));
+}
+
+test "utf8WcwidthCompat" {
+    // 👍🏽 thumbs up: medium skin tone
+    const thumbs_up = "\u{1F44D}\u{1F3FD}";
+    try std.testing.expectEqual(4, utf8WcwidthCompat(thumbs_up, .wcwidth_c));
+    try std.testing.expectEqual(2, utf8WcwidthCompat(thumbs_up, .wcwidth_py));
+    try std.testing.expectEqual(2, utf8WcwidthCompat(thumbs_up, .go_runewidth));
+
+    // ❤️ red heart (text default + VS16)
+    const heart = "\u{2764}\u{FE0F}";
+    try std.testing.expectEqual(1, utf8WcwidthCompat(heart, .wcwidth_c));
+    try std.testing.expectEqual(2, utf8WcwidthCompat(heart, .wcwidth_py));
+    try std.testing.expectEqual(1, utf8WcwidthCompat(heart, .go_runewidth));
+
+    // 👩‍👩‍👧 family: woman, woman, girl
+    const family = "\u{1F469}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+    try std.testing.expectEqual(6, utf8WcwidthCompat(family, .wcwidth_c));
+    try std.testing.expectEqual(2, utf8WcwidthCompat(family, .wcwidth_py));
+    try std.testing.expectEqual(2, utf8WcwidthCompat(family, .go_runewidth));
+
+    try std.testing.expectEqual(3, utf8WcwidthCompat("abc", .wcwidth_c));
+
+    try std.testing.expectEqual(2, utf8WcwidthCompat(thumbs_up, .uniseg));
+    try std.testing.expectEqual(4, utf8WcwidthCompat(thumbs_up, .utf8proc));
+    try std.testing.expectEqual(2, utf8WcwidthCompat(thumbs_up, .unicode_width));
+    try std.testing.expectEqual(2, utf8WcwidthCompat(heart, .uniseg));
+    try std.testing.expectEqual(1, utf8WcwidthCompat(heart, .utf8proc));
+    try std.testing.expectEqual(2, utf8WcwidthCompat(heart, .unicode_width));
+    try std.testing.expectEqual(2, utf8WcwidthCompat(family, .uniseg));
+    try std.testing.expectEqual(6, utf8WcwidthCompat(family, .utf8proc));
+
+    // 🇨🇭 and e + U+0301
+    try std.testing.expectEqual(2, utf8WcwidthCompat("\u{1F1E8}\u{1F1ED}", .uniseg));
+    try std.testing.expectEqual(2, utf8WcwidthCompat("\u{1F1E8}\u{1F1ED}", .utf8proc));
+    try std.testing.expectEqual(1, utf8WcwidthCompat("e\u{0301}", .uniseg));
+    try std.testing.expectEqual(1, utf8WcwidthCompat("e\u{0301}", .utf8proc));
+}
+
 pub fn IteratorNoControl(comptime CodePointIterator: type) type {
     return CustomIterator(
         CodePointIterator,
@@ -1510,3 +3422,175 @@ pub fn isBreakNoControl(
 test "GraphemeBreakTest.txt - isBreakNoControl" {
     try testGraphemeBreakNoControl(isBreakNoControl);
 }
+
+// Legacy grapheme clusters (see UAX #29) are the same as extended grapheme
+// clusters, but without the rules GB9a (SpacingMark), GB9b (Prepend) and GB9c
+// (Indic conjuncts), so spacing marks and prepended characters aren't joined
+// into the cluster, and neither are Indic conjuncts beyond the linker. Since
+// those are the only rules that refer to these values (the Indic linker and
+// extend values are still Extend), treating them as `other` gives the legacy
+// rules.
+fn legacyGraphemeBreak(gb: types.GraphemeBreak) types.GraphemeBreak {
+    return switch (gb) {
+        .spacing_mark, .prepend, .indic_conjunct_break_consonant => .other,
+        else => gb,
+    };
+}
+
+pub fn computeGraphemeBreakLegacy(
+    gb1: types.GraphemeBreak,
+    gb2: types.GraphemeBreak,
+    state: *BreakState,
+) bool {
+    return computeGraphemeBreak(
+        legacyGraphemeBreak(gb1),
+        legacyGraphemeBreak(gb2),
+        state,
+    );
+}
+
+pub fn precomputedGraphemeBreakLegacy(
+    gb1: types.GraphemeBreak,
+    gb2: types.GraphemeBreak,
+    state: *BreakState,
+) bool {
+    const table = comptime buildGraphemeBreakTable(
+        types.GraphemeBreak,
+        BreakState,
+        computeGraphemeBreakLegacy,
+    );
+    // 5 BreakState fields x (20 GraphemeBreak fields)^2 = 2000
+    inlineAssert(@sizeOf(@TypeOf(table)) == 2000);
+    const result = table.get(gb1, gb2, state.*);
+    state.* = result.state;
+    return result.result;
+}
+
+pub fn isBreakLegacy(
+    cp1: u21,
+    cp2: u21,
+    state: *BreakState,
+) bool {
+    const gb1 = get(.grapheme_break, cp1);
+    const gb2 = get(.grapheme_break, cp2);
+    return precomputedGraphemeBreakLegacy(gb1, gb2, state);
+}
+
+pub fn IteratorLegacy(comptime CodePointIterator: type) type {
+    return CustomIterator(
+        CodePointIterator,
+        types.GraphemeBreak,
+        BreakState,
+        .grapheme_break,
+        precomputedGraphemeBreakLegacy,
+    );
+}
+
+pub fn utf8IteratorLegacy(bytes: []const u8) IteratorLegacy(utf8.Iterator) {
+    return IteratorLegacy(utf8.Iterator).init(.init(bytes));
+}
+
+fn testGraphemes(expected: []const []const u8, it: anytype) !void {
+    for (expected) |e| {
+        const g = it.nextGrapheme().?;
+        try std.testing.expectEqualStrings(e, it.next_cp_it.bytes[g.start..g.end]);
+    }
+    try std.testing.expect(it.nextGrapheme() == null);
+}
+
+test "IteratorLegacy spacing marks, prepend and Indic conjuncts" {
+    // Devanagari NA + vowel sign I (SpacingMark)
+    var it = utf8Iterator("\u{0928}\u{093F}");
+    try testGraphemes(&.{"\u{0928}\u{093F}"}, &it);
+    var legacy = utf8IteratorLegacy("\u{0928}\u{093F}");
+    try testGraphemes(&.{ "\u{0928}", "\u{093F}" }, &legacy);
+
+    // Arabic number sign (Prepend) + digits
+    it = utf8Iterator("\u{0600}12");
+    try testGraphemes(&.{ "\u{0600}1", "2" }, &it);
+    legacy = utf8IteratorLegacy("\u{0600}12");
+    try testGraphemes(&.{ "\u{0600}", "1", "2" }, &legacy);
+
+    // Devanagari KA + virama + SSA (Indic conjunct)
+    it = utf8Iterator("\u{0915}\u{094D}\u{0937}");
+    try testGraphemes(&.{"\u{0915}\u{094D}\u{0937}"}, &it);
+    legacy = utf8IteratorLegacy("\u{0915}\u{094D}\u{0937}");
+    try testGraphemes(&.{ "\u{0915}\u{094D}", "\u{0937}" }, &legacy);
+
+    // Other rules are the same
+    legacy = utf8IteratorLegacy("e\u{0301}👩🏽‍🚀🇨🇭\r\n");
+    try testGraphemes(&.{ "e\u{0301}", "👩🏽‍🚀", "🇨🇭", "\r\n" }, &legacy);
+}
+
+test "GraphemeBreakTest.txt - isBreakLegacy" {
+    // GraphemeBreakTest.txt is for extended grapheme clusters, so only test
+    // lines without any of the values the legacy rules differ on.
+    const comps = @import("config.zig").components;
+
+    const trim = comps.trim;
+    const parseCp = comps.parseCp;
+
+    const allocator = std.testing.allocator;
+    const file_path = "ucd/auxiliary/GraphemeBreakTest.txt";
+
+    const file = try std.Io.Dir.cwd().openFile(std.testing.io, file_path, .{});
+    defer file.close(std.testing.io);
+
+    var buf: [2048]u8 = undefined;
+    var file_reader = file.reader(std.testing.io, &buf);
+    const content = try file_reader.interface.allocRemaining(allocator, .unlimited);
+    defer allocator.free(content);
+
+    var lines = std.mem.splitScalar(u8, content, '\n');
+    var success = true;
+    var n_tested: usize = 0;
+
+    var line_num: usize = 1;
+
+    next_line: while (lines.next()) |line| : (line_num += 1) {
+        const trimmed = trim(line);
+        if (trimmed.len == 0) continue;
+
+        var parts = std.mem.splitScalar(u8, trimmed, ' ');
+        _ = parts.next(); // ÷
+        while (parts.next()) |cp_str| {
+            const gb = get(.grapheme_break, try parseCp(cp_str));
+            if (legacyGraphemeBreak(gb) != gb) continue :next_line;
+            _ = parts.next(); // ÷ or ×
+        }
+
+        parts = std.mem.splitScalar(u8, trimmed, ' ');
+        _ = parts.next(); // ÷
+        var state: BreakState = .default;
+        var cp1 = try parseCp(parts.next().?);
+        var expected_str = parts.next().?;
+
+        while (parts.next()) |cp_str| {
+            const cp2 = try parseCp(cp_str);
+            var expected_is_break = std.mem.eql(u8, expected_str, "÷");
+            // See the comment in `testGraphemeBreak`.
+            if (get(.grapheme_break, cp2) == .emoji_modifier and
+                get(.grapheme_break, cp1) != .emoji_modifier_base)
+            {
+                expected_is_break = true;
+            }
+
+            if (isBreakLegacy(cp1, cp2, &state) != expected_is_break) {
+                std.log.err("line={d} cp1={x}, cp2={x}: expected={}", .{
+                    line_num,
+                    cp1,
+                    cp2,
+                    expected_is_break,
+                });
+                success = false;
+            }
+
+            cp1 = cp2;
+            expected_str = parts.next().?;
+        }
+        n_tested += 1;
+    }
+
+    try std.testing.expect(success);
+    try std.testing.expect(n_tested > 0);
+}
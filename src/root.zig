@@ -6,6 +6,9 @@ pub const ascii = @import("ascii.zig");
 pub const grapheme = @import("grapheme.zig");
 pub const code_point = @import("code_point.zig");
 pub const utf8 = @import("utf8.zig");
+pub const x = @import("x.zig");
+pub const fmt = @import("fmt.zig");
+pub const emoji = @import("emoji.zig");
 const testing = std.testing;
 
 pub const FieldEnum = getpkg.FieldEnum;
@@ -23,6 +26,9 @@ test {
     _ = grapheme;
     _ = code_point;
     _ = utf8;
+    _ = x;
+    _ = fmt;
+    _ = emoji;
 }
 
 test "name" {
@@ -534,6 +540,66 @@ test "wcwidth_standalone combining marks are width 1" {
     try testing.expectEqual(1, get(.wcwidth_standalone, 0x20DD)); // COMBINING ENCLOSING CIRCLE (Me)
 }
 
+test "wcwidth_standalone_cjk ambiguous width characters are width 2" {
+    try testing.expectEqual(2, get(.wcwidth_standalone_cjk, 0x00A1)); // INVERTED EXCLAMATION MARK
+    try testing.expectEqual(2, get(.wcwidth_standalone_cjk, 0x2460)); // CIRCLED DIGIT ONE
+    try testing.expectEqual(2, get(.wcwidth_standalone_cjk, 0x25CB)); // WHITE CIRCLE
+    try testing.expectEqual(1, get(.wcwidth_standalone_cjk, 0x00AD)); // SOFT HYPHEN
+    try testing.expectEqual(1, get(.wcwidth_standalone_cjk, 0x0300)); // COMBINING GRAVE ACCENT (Mn)
+    try testing.expectEqual(1, get(.wcwidth_standalone_cjk, 'A'));
+    try testing.expectEqual(0, get(.wcwidth_standalone_cjk, 0x200B)); // ZERO WIDTH SPACE
+}
+
+test "compat_wcwidth_c" {
+    try testing.expectEqual(0, get(.compat_wcwidth_c, 0x0000)); // NULL
+    try testing.expectEqual(0, get(.compat_wcwidth_c, 0x0300)); // COMBINING GRAVE ACCENT (Mn)
+    try testing.expectEqual(0, get(.compat_wcwidth_c, 0x1160)); // HANGUL JUNGSEONG FILLER
+    try testing.expectEqual(1, get(.compat_wcwidth_c, 0x2028)); // LINE SEPARATOR (Zl)
+    try testing.expectEqual(2, get(.compat_wcwidth_c, 0x4E00)); // CJK UNIFIED IDEOGRAPH-4E00
+}
+
+test "compat_wcwidth_py" {
+    try testing.expectEqual(0, get(.compat_wcwidth_py, 0x093E)); // DEVANAGARI VOWEL SIGN AA (Mc)
+    try testing.expectEqual(0, get(.compat_wcwidth_py, 0x2028)); // LINE SEPARATOR (Zl)
+    try testing.expectEqual(0, get(.compat_wcwidth_py, 0xD7B0)); // HANGUL JUNGSEONG O-YEO
+    try testing.expectEqual(2, get(.compat_wcwidth_py, 0xFF21)); // FULLWIDTH LATIN CAPITAL LETTER A
+}
+
+test "compat_go_runewidth" {
+    try testing.expectEqual(0, get(.compat_go_runewidth, 0x0085)); // NEXT LINE (C1)
+    try testing.expectEqual(0, get(.compat_go_runewidth, 0x0301)); // COMBINING ACUTE ACCENT (Mn)
+    try testing.expectEqual(2, get(.compat_go_runewidth, 0x1F600)); // GRINNING FACE
+}
+
+test "compat_unicode_width" {
+    try testing.expectEqual(1, get(.compat_unicode_width, 0x0009)); // CHARACTER TABULATION
+    try testing.expectEqual(0, get(.compat_unicode_width, 0x0300)); // COMBINING GRAVE ACCENT (Mn)
+    try testing.expectEqual(0, get(.compat_unicode_width, 0x00AD)); // SOFT HYPHEN
+    try testing.expectEqual(2, get(.compat_unicode_width, 0x115F)); // HANGUL CHOSEONG FILLER
+    try testing.expectEqual(3, get(.compat_unicode_width, 0x17D8)); // KHMER SIGN BEYYAL
+    try testing.expectEqual(1, get(.compat_unicode_width, 0x0600)); // ARABIC NUMBER SIGN
+    try testing.expectEqual(0, get(.compat_unicode_width, 0x0605)); // ARABIC NUMBER MARK ABOVE
+    try testing.expectEqual(1, get(.compat_unicode_width, 0x1F1E6)); // REGIONAL INDICATOR SYMBOL LETTER A
+}
+
+test "compat_uniseg" {
+    try testing.expectEqual(0, get(.compat_uniseg, 0x0301)); // COMBINING ACUTE ACCENT (Extend)
+    try testing.expectEqual(0, get(.compat_uniseg, 0x200D)); // ZERO WIDTH JOINER
+    try testing.expectEqual(2, get(.compat_uniseg, 0x1F1E6)); // REGIONAL INDICATOR SYMBOL LETTER A
+    try testing.expectEqual(1, get(.compat_uniseg, 0x00A9)); // COPYRIGHT SIGN (text default emoji)
+    try testing.expectEqual(3, get(.compat_uniseg, 0x2E3A)); // TWO-EM DASH
+    try testing.expectEqual(2, get(.compat_uniseg, 0x4E00)); // CJK UNIFIED IDEOGRAPH-4E00
+}
+
+test "compat_utf8proc" {
+    try testing.expectEqual(0, get(.compat_utf8proc, 0x0000)); // NULL
+    try testing.expectEqual(1, get(.compat_utf8proc, 0x00AD)); // SOFT HYPHEN
+    try testing.expectEqual(0, get(.compat_utf8proc, 0x093E)); // DEVANAGARI VOWEL SIGN AA (Mc)
+    try testing.expectEqual(0, get(.compat_utf8proc, 0x2028)); // LINE SEPARATOR (Zl)
+    try testing.expectEqual(1, get(.compat_utf8proc, 0x00E9)); // LATIN SMALL LETTER E WITH ACUTE
+    try testing.expectEqual(2, get(.compat_utf8proc, 0x1F600)); // GRINNING FACE
+}
+
 test "wcwidth_zero_in_grapheme combining marks" {
     // mark_nonspacing (Mn) are true
     try testing.expect(get(.wcwidth_zero_in_grapheme, 0x0300)); // COMBINING GRAVE ACCENT (Mn)
@@ -246,12 +246,30 @@ pub const tables: []const config.Table = &.{
             "script",
         },
     },
+    .{
+        .name = "emoji",
+        .fields = &.{
+            "rgi_emoji_basic",
+            "is_rgi_emoji_keycap_base",
+            "rgi_emoji_flag_pairs",
+            "is_rgi_emoji_modifier_base",
+            "rgi_emoji_tag_sequences",
+            "rgi_emoji_zwj_sequences",
+        },
+    },
     .{
         .name = "wcwidth",
         .fields = &.{
             "grapheme_break_no_control",
             "wcwidth_standalone",
+            "wcwidth_standalone_cjk",
             "wcwidth_zero_in_grapheme",
+            "compat_wcwidth_c",
+            "compat_wcwidth_py",
+            "compat_go_runewidth",
+            "compat_unicode_width",
+            "compat_uniseg",
+            "compat_utf8proc",
         },
     },
 };
@@ -0,0 +1,24 @@
+const config = @import("config.zig");
+
+// A build config without the `rgi_emoji_*` fields, which are the only ones
+// built from `ucd/emoji/emoji-sequences.txt`. See `no_rgi.zig`.
+
+pub const fields = config.fields;
+pub const build_components = config.build_components;
+pub const get_components = config.get_components;
+
+pub const log_level = .debug;
+
+pub const tables: []const config.Table = &.{
+    .{
+        .fields = &.{
+            "grapheme_break",
+            "is_emoji_presentation",
+            "is_emoji_modifier_base",
+            "is_emoji_vs_base",
+            "wcwidth_standalone",
+            "wcwidth_standalone_cjk",
+            "wcwidth_zero_in_grapheme",
+        },
+    },
+};
@@ -0,0 +1,31 @@
+const std = @import("std");
+const uucode = @import("uucode");
+
+// Tests built with `build_config_no_rgi.zig`, making sure grapheme breaking
+// and widths work without the `rgi_emoji_*` fields.
+
+test "no rgi fields" {
+    try std.testing.expect(!uucode.hasField("rgi_emoji_zwj_sequences"));
+    try std.testing.expect(!uucode.hasField("rgi_emoji_basic"));
+}
+
+test "grapheme iterator without rgi fields" {
+    const str = "👩🏽‍🚀🇨🇭e\u{0301}";
+    var it = uucode.grapheme.utf8Iterator(str);
+    var g = it.nextGrapheme().?;
+    try std.testing.expectEqualStrings("👩🏽‍🚀", str[g.start..g.end]);
+    g = it.nextGrapheme().?;
+    try std.testing.expectEqualStrings("🇨🇭", str[g.start..g.end]);
+    g = it.nextGrapheme().?;
+    try std.testing.expectEqualStrings("e\u{0301}", str[g.start..g.end]);
+    try std.testing.expect(it.nextGrapheme() == null);
+}
+
+test "wcwidth without rgi fields" {
+    try std.testing.expectEqual(2, uucode.x.grapheme.utf8Wcwidth("👩🏽‍🚀"));
+    try std.testing.expectEqual(2, uucode.x.grapheme.utf8Wcwidth("🇨🇭"));
+    try std.testing.expectEqual(1, uucode.x.grapheme.utf8Wcwidth("e\u{0301}"));
+    try std.testing.expectEqual(2, uucode.x.grapheme.utf8Wcwidth("\u{2764}\u{FE0F}"));
+    try std.testing.expectEqual(2, uucode.x.grapheme.utf8WcwidthOptions("\u{2460}", .{ .cjk = true }));
+    try std.testing.expectEqual(9, uucode.x.displayWidth("日本\tx", 0, .{}));
+}
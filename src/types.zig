@@ -169,6 +169,23 @@ pub const GraphemeBreakNoControl = enum(u5) {
     indic_conjunct_break_consonant,
 };
 
+// How a code point appears as a `Basic_Emoji` in `emoji-sequences.txt`
+pub const RgiEmojiBasic = enum(u2) {
+    none,
+    // RGI on its own, e.g. U+231A WATCH (default emoji presentation)
+    single,
+    // RGI only when followed by U+FE0F, e.g. U+00A9 COPYRIGHT SIGN FE0F
+    vs16,
+};
+
+// The status field of `emoji-test.txt`
+pub const EmojiStatus = enum(u2) {
+    component,
+    fully_qualified,
+    minimally_qualified,
+    unqualified,
+};
+
 pub const SpecialCasingCondition = enum(u4) {
     none,
     final_sigma,
@@ -81,6 +81,176 @@ pub const Iterator = struct {
         var it = self;
         return it.next();
     }
+
+    // This decodes the code point ending at `i`, moving `i` back to its
+    // start. For valid UTF-8 this gives the same code points as `next` in
+    // reverse. For invalid UTF-8, the last byte is returned as a replacement
+    // character unless it ends a valid sequence, which can differ from `next`
+    // since it consumes the byte that causes the sequence to be rejected.
+    pub fn prev(self: *Self) ?u21 {
+        if (self.i == 0) return null;
+
+        const end = self.i;
+        const min_start = end -| 4;
+        var start = end - 1;
+
+        // Back up over continuation bytes (0b10xxxxxx) to the lead byte.
+        while (start > min_start and self.bytes[start] & 0xC0 == 0x80) {
+            start -= 1;
+        }
+
+        var cp: u21 = 0;
+        var state: usize = UTF8_ACCEPT;
+        var j = start;
+        while (j < end) : (j += 1) {
+            decodeByte(&state, &cp, self.bytes[j]);
+            if (isDoneDecoding(state)) break;
+        }
+
+        if (state == UTF8_ACCEPT and j + 1 == end) {
+            self.i = start;
+            return cp;
+        }
+
+        self.i = end - 1;
+        return 0xFFFD; // Replacement character
+    }
+
+    pub fn peekPrev(self: Self) ?u21 {
+        var it = self;
+        return it.prev();
+    }
+};
+
+// An iterator over UTF-8 stored in a sequence of segments (slices), such as
+// the two halves of a ring buffer or gap buffer, or the pieces of a piece
+// table or rope, decoding code points that span segment boundaries. It gives
+// the same code points as `Iterator` would over the concatenated bytes.
+pub const SegmentedIterator = struct {
+    // This "i" is part of the documented API of this iterator, pointing to the
+    // current location of the iterator as if the segments were concatenated.
+    // See `position` and `positionOf` for (segment, offset) positions.
+    i: usize = 0,
+    segments: []const []const u8,
+    segment: usize = 0,
+    offset: usize = 0,
+
+    const Self = @This();
+
+    pub const Position = struct {
+        segment: usize,
+        offset: usize,
+    };
+
+    pub fn init(segments: []const []const u8) Self {
+        return .{
+            .segments = segments,
+        };
+    }
+
+    pub fn initAt(segments: []const []const u8, pos: Position) Self {
+        var self = init(segments);
+        self.segment = pos.segment;
+        self.offset = pos.offset;
+        self.i = self.indexOf(pos);
+        return self;
+    }
+
+    fn nextByte(self: *Self) ?u8 {
+        while (self.segment < self.segments.len) {
+            const segment = self.segments[self.segment];
+            if (self.offset < segment.len) {
+                const byte = segment[self.offset];
+                self.offset += 1;
+                self.i += 1;
+                return byte;
+            }
+            self.segment += 1;
+            self.offset = 0;
+        }
+        return null;
+    }
+
+    pub fn next(self: *Self) ?u21 {
+        var byte = self.nextByte() orelse return null;
+
+        var cp: u21 = 0;
+        var state: usize = UTF8_ACCEPT;
+
+        while (true) {
+            decodeByte(&state, &cp, byte);
+            if (isDoneDecoding(state)) break;
+            byte = self.nextByte() orelse break;
+        }
+
+        if (state == UTF8_ACCEPT) return cp;
+        return 0xFFFD; // Replacement character
+    }
+
+    pub fn peek(self: Self) ?u21 {
+        var it = self;
+        return it.next();
+    }
+
+    // Returns the (segment, offset) position of `i`, which is in the first
+    // segment that has bytes after it, or at the end of the last segment.
+    pub fn position(self: Self) Position {
+        return self.positionOf(self.i);
+    }
+
+    // Returns the (segment, offset) position of `i`, e.g. the `start` or
+    // `end` of a `grapheme.Grapheme` from iterating these segments.
+    pub fn positionOf(self: Self, i: usize) Position {
+        var start: usize = 0;
+        for (self.segments, 0..) |segment, n| {
+            if (i < start + segment.len) {
+                return .{ .segment = n, .offset = i - start };
+            }
+            start += segment.len;
+        }
+
+        std.debug.assert(i == start);
+        if (self.segments.len == 0) return .{ .segment = 0, .offset = 0 };
+        const last = self.segments.len - 1;
+        return .{ .segment = last, .offset = self.segments[last].len };
+    }
+
+    // Returns the `i` of a (segment, offset) position.
+    pub fn indexOf(self: Self, pos: Position) usize {
+        var i: usize = pos.offset;
+        for (self.segments[0..pos.segment]) |segment| i += segment.len;
+        return i;
+    }
+};
+
+// A decoder for UTF-8 that arrives one byte at a time, such as in chunks read
+// from a stream, where a code point may be split across chunks. It gives the
+// same code points (including replacement characters for invalid UTF-8) as
+// `Iterator` would for the concatenated bytes.
+pub const Decoder = struct {
+    state: usize = UTF8_ACCEPT,
+    cp: u21 = 0,
+
+    // Returns the decoded code point if `byte` completes one.
+    pub fn next(self: *Decoder, byte: u8) ?u21 {
+        decodeByte(&self.state, &self.cp, byte);
+        switch (self.state) {
+            UTF8_ACCEPT => return self.cp,
+            UTF8_REJECT => {
+                self.state = UTF8_ACCEPT;
+                return 0xFFFD; // Replacement character
+            },
+            else => return null,
+        }
+    }
+
+    // Call at the end of the stream, returning a replacement character if the
+    // stream ended partway through a code point.
+    pub fn finish(self: *Decoder) ?u21 {
+        if (self.state == UTF8_ACCEPT) return null;
+        self.state = UTF8_ACCEPT;
+        return 0xFFFD; // Replacement character
+    }
 };
 
 test "Iterator for ascii" {
@@ -119,3 +289,79 @@ test "Iterator overlong utf8" {
     try std.testing.expectEqual(null, it.next());
     try std.testing.expectEqual(null, it.next());
 }
+
+test "Iterator prev" {
+    var it = Iterator.init("a😀é");
+    it.i = it.bytes.len;
+    try std.testing.expectEqual('é', it.peekPrev());
+    try std.testing.expectEqual(7, it.i);
+    try std.testing.expectEqual('é', it.prev());
+    try std.testing.expectEqual(5, it.i);
+    try std.testing.expectEqual(0x1F600, it.prev());
+    try std.testing.expectEqual(1, it.i);
+    try std.testing.expectEqual('a', it.prev());
+    try std.testing.expectEqual(0, it.i);
+    try std.testing.expectEqual(null, it.prev());
+    try std.testing.expectEqual('a', it.next());
+}
+
+test "Iterator prev invalid utf8" {
+    var it = Iterator.init("a\xf0\x80\x80\xafb\x80\x80\x80\x80\xe2\x82");
+    it.i = it.bytes.len;
+    try std.testing.expectEqual(0xFFFD, it.prev()); // \x82
+    try std.testing.expectEqual(0xFFFD, it.prev()); // \xe2
+    for (0..4) |_| try std.testing.expectEqual(0xFFFD, it.prev());
+    try std.testing.expectEqual(6, it.i);
+    try std.testing.expectEqual('b', it.prev());
+    for (0..4) |_| try std.testing.expectEqual(0xFFFD, it.prev());
+    try std.testing.expectEqual('a', it.prev());
+    try std.testing.expectEqual(null, it.prev());
+}
+
+test "Decoder" {
+    const bytes = "a😀\xf0\x80\x80\xafé\xe2\x82";
+    var it = Iterator.init(bytes);
+    var decoder: Decoder = .{};
+    for (bytes) |byte| {
+        if (decoder.next(byte)) |cp| {
+            try std.testing.expectEqual(it.next(), cp);
+        }
+    }
+    try std.testing.expectEqual(it.next(), decoder.finish());
+    try std.testing.expectEqual(null, it.next());
+    try std.testing.expectEqual(null, decoder.finish());
+}
+
+test "SegmentedIterator" {
+    const segments = [_][]const u8{ "a\xf0\x9f", "\x98", "", "\x80é", "" };
+    var it = SegmentedIterator.init(&segments);
+    try std.testing.expectEqual('a', it.next());
+    try std.testing.expectEqual(1, it.i);
+    try std.testing.expectEqual(0x1F600, it.peek());
+    try std.testing.expectEqual(0x1F600, it.next());
+    try std.testing.expectEqual(5, it.i);
+    try std.testing.expectEqual(SegmentedIterator.Position{ .segment = 3, .offset = 1 }, it.position());
+    try std.testing.expectEqual('é', it.next());
+    try std.testing.expectEqual(null, it.next());
+    try std.testing.expectEqual(7, it.i);
+    try std.testing.expectEqual(SegmentedIterator.Position{ .segment = 4, .offset = 0 }, it.position());
+
+    try std.testing.expectEqual(SegmentedIterator.Position{ .segment = 0, .offset = 2 }, it.positionOf(2));
+    try std.testing.expectEqual(3, it.indexOf(.{ .segment = 1, .offset = 0 }));
+    try std.testing.expectEqual(5, it.indexOf(.{ .segment = 3, .offset = 1 }));
+
+    var it2 = SegmentedIterator.initAt(&segments, .{ .segment = 3, .offset = 1 });
+    try std.testing.expectEqual(5, it2.i);
+    try std.testing.expectEqual('é', it2.next());
+}
+
+test "SegmentedIterator invalid utf8 matches Iterator" {
+    const segments = [_][]const u8{ "\xf0\x80", "\x80\xaf\xe2", "\x82" };
+    var it = SegmentedIterator.init(&segments);
+    var expected = Iterator.init("\xf0\x80\x80\xaf\xe2\x82");
+    while (expected.next()) |cp| {
+        try std.testing.expectEqual(cp, it.next().?);
+        try std.testing.expectEqual(expected.i, it.i);
+    }
+    try std.testing.expectEqual(null, it.next());
+}
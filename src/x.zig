@@ -0,0 +1,1162 @@
+const std = @import("std");
+
+const utf8 = @import("utf8.zig");
+const inlineAssert = @import("config.zig").quirks.inlineAssert;
+
+// Extensions that aren't part of the Unicode standard, such as the wcwidth
+// functions in `grapheme` and the display width, truncation and wrapping
+// built on top of them.
+pub const grapheme = @import("grapheme.zig");
+
+pub const ControlDisplay = enum {
+    // Control characters have a width of 0, the same as `wcwidthNext`.
+    zero,
+
+    // C0 control characters and DEL are shown in caret notation (`^[` for
+    // ESC, `^?` for DEL) with a width of 2, and C1 control characters as
+    // `<U+0085>` with a width of 8, the way `less` displays them.
+    caret,
+
+    // All control characters are shown as `<U+001B>` with a width of 8.
+    escape,
+};
+
+pub const DisplayOptions = struct {
+    // Tab characters advance to the next multiple of `tab_width` columns.
+    tab_width: usize = 8,
+
+    controls: ControlDisplay = .zero,
+};
+
+fn isControl(cp: u21) bool {
+    return cp < 0x20 or (cp >= 0x7F and cp <= 0x9F);
+}
+
+fn controlWidth(cp: u21, controls: ControlDisplay) usize {
+    // Line breaks are left to the caller, since the width of a line doesn't
+    // extend past them.
+    if (cp == '\n') return 0;
+
+    return switch (controls) {
+        .zero => 0,
+        .caret => if (cp < 0x20 or cp == 0x7F) 2 else "<U+0085>".len,
+        .escape => "<U+0085>".len,
+    };
+}
+
+// This calculates the display width of the next grapheme when it starts at
+// `column`, advancing the iterator. In contrast to `grapheme.wcwidthNext`,
+// tabs expand to the next tab stop and control characters can optionally be
+// given the width of a visible representation (see `ControlDisplay`).
+pub fn displayWidthNext(it: anytype, column: usize, options: DisplayOptions) usize {
+    return displayWidthNextOptions(it, column, options, .{});
+}
+
+// The same as `displayWidthNext`, but with `grapheme.WcwidthOptions` for the
+// width of graphemes that aren't tabs or control characters.
+pub fn displayWidthNextOptions(
+    it: anytype,
+    column: usize,
+    options: DisplayOptions,
+    comptime wcwidth_options: grapheme.WcwidthOptions,
+) usize {
+    var newline = false;
+    return displayWidthNextImpl(it, column, options, wcwidth_options, &newline);
+}
+
+fn displayWidthNextImpl(
+    it: anytype,
+    column: usize,
+    options: DisplayOptions,
+    comptime wcwidth_options: grapheme.WcwidthOptions,
+    newline: *bool,
+) usize {
+    inlineAssert(@typeInfo(@TypeOf(it)) == .pointer);
+    inlineAssert(options.tab_width > 0);
+
+    const first = it.peekCodePoint() orelse return 0;
+
+    if (first.code_point == '\t') {
+        _ = it.nextCodePoint();
+        return options.tab_width - column % options.tab_width;
+    }
+
+    if (isControl(first.code_point)) {
+        // Controls are always a grapheme on their own, except for "\r\n".
+        var width: usize = 0;
+        while (it.nextCodePoint()) |result| {
+            if (result.code_point == '\n') newline.* = true;
+            width += controlWidth(result.code_point, options.controls);
+            if (result.is_break) break;
+        }
+        return width;
+    }
+
+    return grapheme.wcwidthNextOptions(it, wcwidth_options);
+}
+
+// Returns the display width of the string `s` when it starts at `column`.
+// A '\n' starts a new line at column 0, and for multiple lines the width of
+// the widest one is returned (with the first line measured from `column`).
+pub fn displayWidth(s: []const u8, column: usize, options: DisplayOptions) usize {
+    return displayWidthOptions(s, column, options, .{});
+}
+
+// The same as `displayWidth`, but with `grapheme.WcwidthOptions` for the
+// width of graphemes that aren't tabs or control characters.
+pub fn displayWidthOptions(
+    s: []const u8,
+    column: usize,
+    options: DisplayOptions,
+    comptime wcwidth_options: grapheme.WcwidthOptions,
+) usize {
+    var it = grapheme.utf8Iterator(s);
+    var start = column;
+    var end = column;
+    var max: usize = 0;
+    while (it.next_cp != null) {
+        var newline = false;
+        end += displayWidthNextImpl(&it, end, options, wcwidth_options, &newline);
+        if (newline) {
+            max = @max(max, end - start);
+            start = 0;
+            end = 0;
+        }
+    }
+    return @max(max, end - start);
+}
+
+test "displayWidth tabs" {
+    try std.testing.expectEqual(9, displayWidth("a\tb", 0, .{}));
+    try std.testing.expectEqual(5, displayWidth("a\tb", 0, .{ .tab_width = 4 }));
+    try std.testing.expectEqual(8, displayWidth("\t", 0, .{}));
+    try std.testing.expectEqual(5, displayWidth("\t", 3, .{}));
+    try std.testing.expectEqual(1, displayWidth("\t", 7, .{}));
+    try std.testing.expectEqual(8, displayWidth("\t", 8, .{}));
+    // 日本 is width 4, so the tab only advances 4 columns
+    try std.testing.expectEqual(9, displayWidth("日本\tx", 0, .{}));
+}
+
+test "displayWidth controls" {
+    try std.testing.expectEqual(2, displayWidth("a\x1Bb", 0, .{}));
+    try std.testing.expectEqual(4, displayWidth("a\x1Bb", 0, .{ .controls = .caret }));
+    try std.testing.expectEqual(10, displayWidth("a\x1Bb", 0, .{ .controls = .escape }));
+    try std.testing.expectEqual(2, displayWidth("\x7F", 0, .{ .controls = .caret }));
+    try std.testing.expectEqual(8, displayWidth("\u{0085}", 0, .{ .controls = .caret }));
+    try std.testing.expectEqual(3, displayWidth("a\r\n", 0, .{ .controls = .caret }));
+    try std.testing.expectEqual(1, displayWidth("a\n", 0, .{ .controls = .escape }));
+}
+
+test "displayWidth newlines" {
+    try std.testing.expectEqual(3, displayWidth("ab\nabc", 0, .{}));
+    try std.testing.expectEqual(4, displayWidth("ab\nc", 2, .{}));
+    // The tab after the newline starts from column 0 again
+    try std.testing.expectEqual(9, displayWidth("abc\n\tx", 0, .{}));
+    try std.testing.expectEqual(9, displayWidth("\tx", 5, .{}));
+    try std.testing.expectEqual(9, displayWidth("x\n\tx", 5, .{}));
+    try std.testing.expectEqual(8, displayWidth("a\r\n\t", 0, .{ .controls = .caret }));
+    try std.testing.expectEqual(0, displayWidth("\n\n", 0, .{}));
+}
+
+test "displayWidthOptions" {
+    try std.testing.expectEqual(1, displayWidth("\u{2460}", 0, .{}));
+    try std.testing.expectEqual(2, displayWidthOptions("\u{2460}", 0, .{}, .{ .cjk = true }));
+    try std.testing.expectEqual(4, displayWidthOptions("\u{2460}\t", 4, .{ .tab_width = 4 }, .{ .cjk = true }));
+}
+
+test "displayWidthNext" {
+    var it = grapheme.utf8Iterator("\tA\u{0300}\t");
+    try std.testing.expectEqual(4, displayWidthNext(&it, 4, .{}));
+    try std.testing.expectEqual(1, displayWidthNext(&it, 8, .{}));
+    try std.testing.expectEqual(7, displayWidthNext(&it, 9, .{}));
+    try std.testing.expect(it.peekCodePoint() == null);
+    try std.testing.expectEqual(0, displayWidthNext(&it, 16, .{}));
+}
+
+test "displayWidthNextOptions" {
+    var it = grapheme.utf8Iterator("\u{2460}\t");
+    try std.testing.expectEqual(2, displayWidthNextOptions(&it, 0, .{}, .{ .cjk = true }));
+    try std.testing.expectEqual(6, displayWidthNextOptions(&it, 2, .{}, .{ .cjk = true }));
+}
+
+pub const Token = struct {
+    kind: Kind,
+    start: usize,
+    end: usize,
+
+    pub const Kind = enum {
+        grapheme,
+
+        // Control Sequence Introducer: `ESC [` (or C1 `0x9B`), parameters,
+        // intermediates, and a final byte. For example, SGR `ESC [ 3 1 m`.
+        csi,
+
+        // Operating System Command: `ESC ]` (or C1 `0x9D`) terminated by ST
+        // (`ESC \` or C1 `0x9C`) or BEL. For example, OSC 8 hyperlinks.
+        osc,
+
+        // Device Control String: `ESC P` (or C1 `0x90`) terminated by ST.
+        dcs,
+
+        // Application Program Command: `ESC _` (or C1 `0x9F`) terminated
+        // by ST. Also used for privacy messages (`ESC ^`) and start of string
+        // (`ESC X`), which have the same form.
+        apc,
+
+        // Single shifts: `ESC N` / `ESC O` (or C1 `0x8E` / `0x8F`) followed
+        // by a single character.
+        ss2,
+        ss3,
+
+        // Any other escape sequence: `ESC`, intermediates, and a final byte,
+        // for example `ESC ( B` or `ESC 7`.
+        escape,
+    };
+};
+
+// An adapter over a grapheme `Iterator` that recognizes ECMA-48 escape
+// sequences and yields them as separate tokens from the grapheme clusters,
+// so that they can be given a width of zero. The grapheme iterator `it` is
+// part of the API, for calling `grapheme.wcwidthNext` and similar when not
+// at an escape sequence (see `nextEscape`).
+pub fn EscapeIterator(comptime CodePointIterator: type) type {
+    return struct {
+        it: grapheme.Iterator(CodePointIterator),
+
+        const Self = @This();
+
+        pub fn init(cp_it: CodePointIterator) Self {
+            return .{ .it = .init(cp_it) };
+        }
+
+        // Consumes the next grapheme or escape sequence.
+        pub fn next(self: *Self) ?Token {
+            if (self.nextEscape()) |token| return token;
+            const g = self.it.nextGrapheme() orelse return null;
+            return .{ .kind = .grapheme, .start = g.start, .end = g.end };
+        }
+
+        pub fn peek(self: Self) ?Token {
+            var it = self;
+            return it.next();
+        }
+
+        // If the iterator is at the start of an escape sequence, this
+        // consumes and returns it, otherwise it returns null without
+        // advancing.
+        pub fn nextEscape(self: *Self) ?Token {
+            const start = self.it.i;
+            const first = self.it.peekCodePoint() orelse return null;
+
+            const kind: Token.Kind = switch (first.code_point) {
+                0x1B => blk: {
+                    _ = self.it.nextCodePoint();
+                    const second = self.it.peekCodePoint() orelse
+                        break :blk .escape;
+                    const kind: Token.Kind = switch (second.code_point) {
+                        '[' => .csi,
+                        ']' => .osc,
+                        'P' => .dcs,
+                        '_', '^', 'X' => .apc,
+                        'N' => .ss2,
+                        'O' => .ss3,
+                        else => {
+                            self.skipEscapeRest();
+                            break :blk .escape;
+                        },
+                    };
+                    _ = self.it.nextCodePoint();
+                    break :blk kind;
+                },
+                0x9B => .csi,
+                0x9D => .osc,
+                0x90 => .dcs,
+                0x9F, 0x9E, 0x98 => .apc,
+                0x8E => .ss2,
+                0x8F => .ss3,
+                else => return null,
+            };
+
+            if (first.code_point != 0x1B) {
+                _ = self.it.nextCodePoint();
+            }
+
+            switch (kind) {
+                .csi => self.skipCsiRest(),
+                .osc, .dcs, .apc => self.skipStringRest(kind == .osc),
+                .ss2, .ss3 => _ = self.it.nextCodePoint(),
+                .escape, .grapheme => {},
+            }
+
+            return .{ .kind = kind, .start = start, .end = self.it.i };
+        }
+
+        fn skipEscapeRest(self: *Self) void {
+            while (self.it.peekCodePoint()) |result| {
+                const cp = result.code_point;
+                if (cp >= 0x20 and cp <= 0x2F) {
+                    _ = self.it.nextCodePoint();
+                } else {
+                    if (cp >= 0x30 and cp <= 0x7E) _ = self.it.nextCodePoint();
+                    return;
+                }
+            }
+        }
+
+        fn skipCsiRest(self: *Self) void {
+            while (self.it.peekCodePoint()) |result| {
+                const cp = result.code_point;
+                // Anything outside of the parameter, intermediate and final
+                // byte ranges ends the (malformed) sequence without being
+                // consumed.
+                if (cp < 0x20 or cp > 0x7E) return;
+                _ = self.it.nextCodePoint();
+                if (cp >= 0x40) return;
+            }
+        }
+
+        fn skipStringRest(self: *Self, allow_bel: bool) void {
+            while (self.it.nextCodePoint()) |result| {
+                switch (result.code_point) {
+                    0x9C => return,
+                    0x07 => if (allow_bel) return,
+                    0x1B => {
+                        const next = self.it.peekCodePoint() orelse return;
+                        if (next.code_point == '\\') {
+                            _ = self.it.nextCodePoint();
+                            return;
+                        }
+                    },
+                    else => {},
+                }
+            }
+        }
+    };
+}
+
+pub fn utf8EscapeIterator(bytes: []const u8) EscapeIterator(utf8.Iterator) {
+    return EscapeIterator(utf8.Iterator).init(.init(bytes));
+}
+
+// Returns the width of `s`, the same as `grapheme.utf8Wcwidth` but with
+// escape sequences having a width of 0.
+pub fn escapeAwareWidth(s: []const u8) usize {
+    var it = utf8EscapeIterator(s);
+    var width: usize = 0;
+    while (it.it.next_cp != null) {
+        if (it.nextEscape() == null) {
+            width += grapheme.wcwidthNext(&it.it);
+        }
+    }
+    return width;
+}
+
+test "EscapeIterator" {
+    const str = "\x1B[1;31mé\x1B]8;;https://example.com\x1B\\link\x1B]8;;\x07\x1B(B😀";
+    var it = utf8EscapeIterator(str);
+
+    var token = it.next().?;
+    try std.testing.expectEqual(.csi, token.kind);
+    try std.testing.expectEqualStrings("\x1B[1;31m", str[token.start..token.end]);
+
+    token = it.next().?;
+    try std.testing.expectEqual(.grapheme, token.kind);
+    try std.testing.expectEqualStrings("é", str[token.start..token.end]);
+
+    token = it.next().?;
+    try std.testing.expectEqual(.osc, token.kind);
+    try std.testing.expectEqualStrings("\x1B]8;;https://example.com\x1B\\", str[token.start..token.end]);
+
+    for ("link") |c| {
+        token = it.next().?;
+        try std.testing.expectEqual(.grapheme, token.kind);
+        try std.testing.expectEqual(c, str[token.start]);
+    }
+
+    token = it.next().?;
+    try std.testing.expectEqual(.osc, token.kind);
+    try std.testing.expectEqualStrings("\x1B]8;;\x07", str[token.start..token.end]);
+
+    token = it.next().?;
+    try std.testing.expectEqual(.escape, token.kind);
+    try std.testing.expectEqualStrings("\x1B(B", str[token.start..token.end]);
+
+    token = it.peek().?;
+    try std.testing.expectEqual(.grapheme, token.kind);
+    token = it.next().?;
+    try std.testing.expectEqualStrings("😀", str[token.start..token.end]);
+
+    try std.testing.expect(it.next() == null);
+}
+
+test "EscapeIterator DCS, APC, SS3 and C1" {
+    const str = "\x1BPq#0\x1B\\\x1B_Gf=24\x1B\\\x1BOPa\u{009B}2Jb";
+    var it = utf8EscapeIterator(str);
+
+    try std.testing.expectEqual(.dcs, it.next().?.kind);
+    try std.testing.expectEqual(.apc, it.next().?.kind);
+
+    var token = it.next().?;
+    try std.testing.expectEqual(.ss3, token.kind);
+    try std.testing.expectEqualStrings("\x1BOP", str[token.start..token.end]);
+
+    try std.testing.expectEqual(.grapheme, it.next().?.kind);
+
+    token = it.next().?;
+    try std.testing.expectEqual(.csi, token.kind);
+    try std.testing.expectEqualStrings("\u{009B}2J", str[token.start..token.end]);
+
+    try std.testing.expectEqual(.grapheme, it.next().?.kind);
+    try std.testing.expect(it.next() == null);
+}
+
+test "escapeAwareWidth" {
+    try std.testing.expectEqual(4, grapheme.utf8Wcwidth("\x1B[31m"));
+    try std.testing.expectEqual(0, escapeAwareWidth("\x1B[31m"));
+    try std.testing.expectEqual(5, escapeAwareWidth("\x1B[1;31m日本\x1B[0m!\x1B]8;;http://a.b\x07"));
+    // An unterminated sequence extends to the end of the string
+    try std.testing.expectEqual(2, escapeAwareWidth("ab\x1B]0;title"));
+    try std.testing.expectEqual(0, escapeAwareWidth("\x1B"));
+}
+
+pub const Elide = enum {
+    // Keep the start of the string, eliding the end: "hello w…"
+    end,
+
+    // Keep the end of the string, eliding the start: "…o world"
+    start,
+
+    // Keep both the start and the end, eliding the middle: "hell…rld"
+    middle,
+};
+
+// The result of `truncate`, made up of slices of the original string (and
+// the ellipsis). Use `format` (with "{f}") to write out the truncated string.
+pub const Truncated = struct {
+    prefix: []const u8,
+    suffix: []const u8,
+
+    // Empty if the string wasn't truncated.
+    ellipsis: []const u8,
+
+    // The number of spaces to pad with (next to the ellipsis) when a wide
+    // grapheme would have straddled the limit.
+    padding: usize,
+
+    elide: Elide,
+
+    // The display width of the truncated string, including the padding and
+    // ellipsis.
+    width: usize,
+
+    pub fn format(self: Truncated, writer: *std.Io.Writer) std.Io.Writer.Error!void {
+        try writer.writeAll(self.prefix);
+        if (self.elide != .start) try writer.splatByteAll(' ', self.padding);
+        try writer.writeAll(self.ellipsis);
+        if (self.elide == .start) try writer.splatByteAll(' ', self.padding);
+        try writer.writeAll(self.suffix);
+    }
+};
+
+// Returns the end of the longest prefix of `s` made up of whole grapheme
+// clusters that fits within `max_columns`, along with its width.
+fn fittingPrefix(s: []const u8, max_columns: usize) struct { end: usize, width: usize } {
+    var it = grapheme.utf8Iterator(s);
+    var width: usize = 0;
+    var end: usize = 0;
+    while (it.next_cp != null) {
+        const w = grapheme.wcwidthNext(&it);
+        if (width + w > max_columns) break;
+        width += w;
+        end = it.i;
+    }
+    return .{ .end = end, .width = width };
+}
+
+// Returns the start of the longest suffix of `s` made up of whole grapheme
+// clusters that fits within `max_columns` (and doesn't start before
+// `min_start`), along with its width.
+fn fittingSuffix(s: []const u8, max_columns: usize, min_start: usize) struct { start: usize, width: usize } {
+    const total = grapheme.utf8Wcwidth(s[min_start..]);
+    var it = grapheme.utf8Iterator(s[min_start..]);
+    var width = total;
+    while (width > max_columns) {
+        width -= grapheme.wcwidthNext(&it);
+    }
+    return .{ .start = min_start + it.i, .width = width };
+}
+
+// Truncates `s` to fit within `max_columns` (as measured by
+// `grapheme.wcwidthNext`), eliding part of the string and showing
+// `ellipsis` in its place. Grapheme clusters are never split, and if a wide
+// grapheme would straddle the limit, a space is used instead. If `s`
+// already fits, it's returned as the `prefix` with no ellipsis.
+pub fn truncate(s: []const u8, max_columns: usize, ellipsis: []const u8, elide: Elide) Truncated {
+    const total = grapheme.utf8Wcwidth(s);
+    if (total <= max_columns) {
+        return .{
+            .prefix = s,
+            .suffix = "",
+            .ellipsis = "",
+            .padding = 0,
+            .elide = elide,
+            .width = total,
+        };
+    }
+
+    const ellipsis_width = grapheme.utf8Wcwidth(ellipsis);
+    if (ellipsis_width > max_columns) {
+        return truncate(ellipsis, max_columns, "", .end);
+    }
+
+    const available = max_columns - ellipsis_width;
+    var result: Truncated = .{
+        .prefix = "",
+        .suffix = "",
+        .ellipsis = ellipsis,
+        .padding = 0,
+        .elide = elide,
+        .width = max_columns,
+    };
+
+    var used: usize = 0;
+    switch (elide) {
+        .end => {
+            const prefix = fittingPrefix(s, available);
+            result.prefix = s[0..prefix.end];
+            used = prefix.width;
+        },
+        .start => {
+            const suffix = fittingSuffix(s, available, 0);
+            result.suffix = s[suffix.start..];
+            used = suffix.width;
+        },
+        .middle => {
+            const prefix = fittingPrefix(s, available - available / 2);
+            const suffix = fittingSuffix(s, available - prefix.width, prefix.end);
+            result.prefix = s[0..prefix.end];
+            result.suffix = s[suffix.start..];
+            used = prefix.width + suffix.width;
+        },
+    }
+
+    result.padding = available - used;
+    return result;
+}
+
+fn testTruncate(expected: []const u8, s: []const u8, max_columns: usize, ellipsis: []const u8, elide: Elide) !void {
+    var buffer: [64]u8 = undefined;
+    const t = truncate(s, max_columns, ellipsis, elide);
+    const actual = try std.fmt.bufPrint(&buffer, "{f}", .{t});
+    try std.testing.expectEqualStrings(expected, actual);
+    try std.testing.expectEqual(grapheme.utf8Wcwidth(actual), t.width);
+}
+
+test "truncate" {
+    try testTruncate("hello w…", "hello world", 8, "…", .end);
+    try testTruncate("…o world", "hello world", 8, "…", .start);
+    try testTruncate("hell…rld", "hello world", 8, "…", .middle);
+    try testTruncate("hello...", "hello world", 8, "...", .end);
+    try testTruncate("hi", "hi", 5, "…", .end);
+    try testTruncate("hello world", "hello world", 11, "…", .middle);
+    try testTruncate("", "hello", 0, "…", .end);
+    try testTruncate("..", "hello", 2, "...", .end);
+}
+
+test "truncate wide graphemes straddling the limit" {
+    try testTruncate("日本 …", "日本語テキスト", 6, "…", .end);
+    try testTruncate("… スト", "日本語テキスト", 6, "…", .start);
+    try testTruncate("日 …ト", "日本語テキスト", 6, "…", .middle);
+
+    const t = truncate("日本語", 4, "…", .end);
+    try std.testing.expectEqualStrings("日", t.prefix);
+    try std.testing.expectEqual(1, t.padding);
+}
+
+test "truncate doesn't split grapheme clusters" {
+    try testTruncate("e\u{0301}…", "e\u{0301}e\u{0301}e\u{0301}", 2, "…", .end);
+    try testTruncate("…👨🏻‍❤️‍👨🏿", "ab👨🏻‍❤️‍👨🏿", 3, "…", .start);
+    try testTruncate("a …", "a👨🏻‍❤️‍👨🏿b", 3, "…", .end);
+}
+
+// A line produced by `wrap`, which is `s[start..end]` of the wrapped string,
+// excluding trailing spaces and line breaks. Soft hyphens (U+00AD) within a
+// line should be rendered invisibly, and if the line was broken at a soft
+// hyphen then `hyphen` is true and a hyphen should be shown at the end of
+// the line (its width is included in `width`).
+pub const Line = struct {
+    start: usize,
+    end: usize,
+    width: usize,
+    hyphen: bool,
+};
+
+pub const WrapOptions = struct {
+    // Rather than fitting as much as possible on each line, choose the line
+    // breaks that minimize the sum of the squares of the unused columns at
+    // the end of each line (except the last line of each paragraph), giving
+    // a less ragged right edge.
+    optimal: bool = false,
+};
+
+const BreakClass = enum {
+    none,
+    space,
+    newline,
+    soft_hyphen,
+    zero_width_space,
+    hyphen,
+    wide,
+    other,
+};
+
+fn breakClass(cp: u21, width: usize) BreakClass {
+    return switch (cp) {
+        '\n', '\r', 0x0B, 0x0C, 0x85, 0x2028, 0x2029 => .newline,
+        '\t',
+        ' ',
+        0x1680,
+        0x2000...0x2006,
+        0x2008...0x200A,
+        0x205F,
+        0x3000,
+        => .space,
+        0x00AD => .soft_hyphen,
+        0x200B => .zero_width_space,
+        '-', 0x2010, 0x2012, 0x2013 => .hyphen,
+        else => if (width == 2) .wide else .other,
+    };
+}
+
+// This is a small subset of the rules of UAX #14 (Unicode Line Breaking
+// Algorithm): breaks are allowed after spaces (but not no-break spaces),
+// hyphens, soft hyphens and zero width spaces, and around wide characters
+// such as CJK ideographs, and required after line breaks.
+fn isBreakBefore(prev: BreakClass, class: BreakClass) bool {
+    if (prev == .none) return false;
+    if (prev == .newline) return true;
+
+    return switch (class) {
+        .space, .newline, .soft_hyphen, .zero_width_space => false,
+        else => switch (prev) {
+            .space, .soft_hyphen, .zero_width_space, .wide => true,
+            .hyphen => class != .hyphen,
+            .other => class == .wide,
+            .none, .newline => unreachable,
+        },
+    };
+}
+
+// The text between two break opportunities.
+const WrapUnit = struct {
+    start: usize,
+
+    // The end of the content, excluding trailing spaces, soft hyphens and
+    // line breaks.
+    end: usize,
+
+    width: usize,
+
+    // The width of the trailing spaces, which only counts if the line
+    // continues past this unit.
+    space_width: usize,
+
+    // Whether a hyphen is shown if the line is broken after this unit.
+    hyphen: bool,
+
+    // Whether the line must be broken after this unit.
+    mandatory: bool,
+
+    fn advance(self: WrapUnit) usize {
+        return self.width + self.space_width;
+    }
+
+    fn endWidth(self: WrapUnit) usize {
+        return self.width + @intFromBool(self.hyphen);
+    }
+};
+
+fn appendWrapUnit(
+    allocator: std.mem.Allocator,
+    units: *std.ArrayList(WrapUnit),
+    s: []const u8,
+    unit: WrapUnit,
+    max_columns: usize,
+) std.mem.Allocator.Error!void {
+    if (unit.width <= max_columns) return units.append(allocator, unit);
+
+    // Words that are too wide for a line on their own are split at grapheme
+    // boundaries.
+    var it = grapheme.utf8Iterator(s[unit.start..unit.end]);
+    var chunk_start: usize = 0;
+    var chunk_width: usize = 0;
+    while (it.peekGrapheme()) |g| {
+        const width = grapheme.wcwidthNext(&it);
+        if (chunk_width > 0 and chunk_width + width > max_columns) {
+            try units.append(allocator, .{
+                .start = unit.start + chunk_start,
+                .end = unit.start + g.start,
+                .width = chunk_width,
+                .space_width = 0,
+                .hyphen = false,
+                .mandatory = false,
+            });
+            chunk_start = g.start;
+            chunk_width = 0;
+        }
+        chunk_width += width;
+    }
+
+    var last = unit;
+    last.start = unit.start + chunk_start;
+    last.width = chunk_width;
+    try units.append(allocator, last);
+}
+
+fn wrapUnits(
+    allocator: std.mem.Allocator,
+    s: []const u8,
+    max_columns: usize,
+) std.mem.Allocator.Error![]WrapUnit {
+    var units: std.ArrayList(WrapUnit) = .empty;
+    errdefer units.deinit(allocator);
+
+    var it = grapheme.utf8Iterator(s);
+    var unit: WrapUnit = .{
+        .start = 0,
+        .end = 0,
+        .width = 0,
+        .space_width = 0,
+        .hyphen = false,
+        .mandatory = false,
+    };
+    var prev: BreakClass = .none;
+
+    while (it.peekGrapheme()) |g| {
+        const cp = it.peekCodePoint().?.code_point;
+        const width = grapheme.wcwidthNext(&it);
+        const class = breakClass(cp, width);
+
+        if (isBreakBefore(prev, class)) {
+            try appendWrapUnit(allocator, &units, s, unit, max_columns);
+            unit = .{
+                .start = g.start,
+                .end = g.start,
+                .width = 0,
+                .space_width = 0,
+                .hyphen = false,
+                .mandatory = false,
+            };
+        }
+
+        switch (class) {
+            .space => unit.space_width += width,
+            .newline => {
+                unit.mandatory = true;
+                unit.hyphen = false;
+            },
+            .soft_hyphen => unit.hyphen = true,
+            .zero_width_space => {},
+            .hyphen, .wide, .other => {
+                unit.width += width;
+                unit.end = g.end;
+            },
+            .none => unreachable,
+        }
+        prev = class;
+    }
+
+    if (prev != .none) {
+        // There's no break after the last unit to show a hyphen at.
+        unit.hyphen = false;
+        try appendWrapUnit(allocator, &units, s, unit, max_columns);
+    }
+
+    return units.toOwnedSlice(allocator);
+}
+
+fn wrapLine(units: []const WrapUnit, first: usize, last: usize) Line {
+    var width: usize = 0;
+    for (units[first..last]) |unit| width += unit.advance();
+    return .{
+        .start = units[first].start,
+        .end = units[last].end,
+        .width = width + units[last].endWidth(),
+        .hyphen = units[last].hyphen,
+    };
+}
+
+fn wrapGreedy(
+    allocator: std.mem.Allocator,
+    lines: *std.ArrayList(Line),
+    units: []const WrapUnit,
+    max_columns: usize,
+) std.mem.Allocator.Error!void {
+    var first: usize = 0;
+    while (first < units.len) {
+        var last = first;
+        var width = units[first].advance();
+        while (!units[last].mandatory and
+            last + 1 < units.len and
+            width + units[last + 1].endWidth() <= max_columns)
+        {
+            last += 1;
+            width += units[last].advance();
+        }
+        try lines.append(allocator, wrapLine(units, first, last));
+        first = last + 1;
+    }
+}
+
+fn wrapOptimal(
+    allocator: std.mem.Allocator,
+    lines: *std.ArrayList(Line),
+    units: []const WrapUnit,
+    max_columns: usize,
+) std.mem.Allocator.Error!void {
+    // `cost[j]` is the lowest cost of the lines for the paragraph up to (but
+    // not including) unit `j`, and `line_start[j]` is the first unit of the
+    // last of those lines.
+    const cost = try allocator.alloc(usize, units.len + 1);
+    defer allocator.free(cost);
+    const line_start = try allocator.alloc(usize, units.len + 1);
+    defer allocator.free(line_start);
+
+    var paragraph_start: usize = 0;
+    while (paragraph_start < units.len) {
+        var paragraph_last = paragraph_start;
+        while (!units[paragraph_last].mandatory and paragraph_last + 1 < units.len) {
+            paragraph_last += 1;
+        }
+        const paragraph_end = paragraph_last + 1;
+
+        cost[paragraph_start] = 0;
+        for (paragraph_start + 1..paragraph_end + 1) |j| {
+            cost[j] = std.math.maxInt(usize);
+            var i = j - 1;
+            var width = units[i].endWidth();
+            while (true) {
+                // A single unit may be too wide (e.g. a wide grapheme wider
+                // than `max_columns`), but otherwise the line must fit.
+                if (width > max_columns and i < j - 1) break;
+
+                const slack = max_columns -| width;
+                const badness = if (j == paragraph_end) 0 else slack * slack;
+                const c = cost[i] +| badness;
+                if (c < cost[j]) {
+                    cost[j] = c;
+                    line_start[j] = i;
+                }
+
+                if (i == paragraph_start) break;
+                i -= 1;
+                width += units[i].advance();
+            }
+        }
+
+        const first_line = lines.items.len;
+        var j = paragraph_end;
+        while (j > paragraph_start) {
+            try lines.append(allocator, wrapLine(units, line_start[j], j - 1));
+            j = line_start[j];
+        }
+        std.mem.reverse(Line, lines.items[first_line..]);
+
+        paragraph_start = paragraph_end;
+    }
+}
+
+// Wraps `s` to lines that fit within `max_columns` (as measured by
+// `grapheme.wcwidthNext`), returning the lines, which are owned by the
+// caller. Lines are broken at break opportunities (see `isBreakBefore`),
+// falling back to grapheme boundaries for words too wide to fit on a line.
+pub fn wrap(
+    allocator: std.mem.Allocator,
+    s: []const u8,
+    max_columns: usize,
+    options: WrapOptions,
+) std.mem.Allocator.Error![]Line {
+    const units = try wrapUnits(allocator, s, max_columns);
+    defer allocator.free(units);
+
+    var lines: std.ArrayList(Line) = .empty;
+    errdefer lines.deinit(allocator);
+
+    if (options.optimal) {
+        try wrapOptimal(allocator, &lines, units, max_columns);
+    } else {
+        try wrapGreedy(allocator, &lines, units, max_columns);
+    }
+
+    return lines.toOwnedSlice(allocator);
+}
+
+fn testWrap(expected: []const []const u8, s: []const u8, max_columns: usize, options: WrapOptions) !void {
+    const allocator = std.testing.allocator;
+    const lines = try wrap(allocator, s, max_columns, options);
+    defer allocator.free(lines);
+
+    try std.testing.expectEqual(expected.len, lines.len);
+    for (expected, lines) |e, line| {
+        const actual = s[line.start..line.end];
+        try std.testing.expectEqualStrings(e, actual);
+        try std.testing.expectEqual(
+            grapheme.utf8Wcwidth(actual) + @intFromBool(line.hyphen),
+            line.width,
+        );
+    }
+}
+
+test "wrap" {
+    try testWrap(&.{ "the quick", "brown fox" }, "the quick brown fox", 10, .{});
+    try testWrap(&.{ "aaa bb", "cc", "ddddd" }, "aaa bb cc ddddd", 6, .{});
+    try testWrap(&.{ "a", "b c" }, "a\nb c", 10, .{});
+    try testWrap(&.{ "a", "", "b" }, "a\r\n\nb  ", 10, .{});
+    try testWrap(&.{ "well-", "known", "fact" }, "well-known fact", 6, .{});
+    try testWrap(&.{ "well-known", "fact" }, "well-known fact", 12, .{});
+    try testWrap(&.{ "a\u{00A0}b", "c" }, "a\u{00A0}b c", 3, .{});
+    try testWrap(&.{}, "", 10, .{});
+}
+
+test "wrap optimal" {
+    try testWrap(&.{ "aaa", "bb cc", "ddddd" }, "aaa bb cc ddddd", 6, .{ .optimal = true });
+    try testWrap(&.{ "a", "b c" }, "a\nb c", 10, .{ .optimal = true });
+    try testWrap(&.{ "abcd", "efgh", "ij" }, "abcdefghij", 4, .{ .optimal = true });
+}
+
+test "wrap overlong words and wide characters" {
+    try testWrap(&.{ "abcd", "efgh", "ij" }, "abcdefghij", 4, .{});
+    try testWrap(&.{ "foo", "abcd", "efgh", "ij" }, "foo abcdefghij", 4, .{});
+    try testWrap(&.{ "日本", "語で", "す" }, "日本語です", 5, .{});
+    try testWrap(&.{ "e\u{0301}e\u{0301}", "e\u{0301}" }, "e\u{0301}e\u{0301}e\u{0301}", 2, .{});
+    // A grapheme wider than the line gets a line to itself
+    try testWrap(&.{ "日", "本" }, "日本", 1, .{});
+}
+
+test "wrap soft hyphen" {
+    const allocator = std.testing.allocator;
+    const s = "hyphen\u{00AD}ation";
+
+    const lines = try wrap(allocator, s, 8, .{});
+    defer allocator.free(lines);
+    try std.testing.expectEqual(2, lines.len);
+    try std.testing.expectEqualStrings("hyphen", s[lines[0].start..lines[0].end]);
+    try std.testing.expect(lines[0].hyphen);
+    try std.testing.expectEqual(7, lines[0].width);
+    try std.testing.expectEqualStrings("ation", s[lines[1].start..lines[1].end]);
+    try std.testing.expect(!lines[1].hyphen);
+
+    // No hyphen is shown (or measured) when the line isn't broken there
+    const one_line = try wrap(allocator, s, 20, .{});
+    defer allocator.free(one_line);
+    try std.testing.expectEqual(1, one_line.len);
+    try std.testing.expectEqualStrings(s, s[one_line[0].start..one_line[0].end]);
+    try std.testing.expect(!one_line[0].hyphen);
+    try std.testing.expectEqual(11, one_line[0].width);
+}
+
+// Maps grapheme clusters (as code point sequences) to compact IDs, e.g. for
+// storing one grapheme cluster per terminal cell. Single code point clusters
+// (by far the most common) take a fast path, using the code point itself as
+// the ID with no storage or ref-counting. Multi code point clusters are
+// stored once, ref-counted, and given IDs above `max_code_point_id`.
+pub const GraphemeInterner = struct {
+    entries: std.ArrayList(Entry) = .empty,
+
+    // Indices of `entries` that have been released and can be reused.
+    free_list: std.ArrayList(u32) = .empty,
+
+    map: std.HashMapUnmanaged(
+        []const u21,
+        Id,
+        CodePointsContext,
+        std.hash_map.default_max_load_percentage,
+    ) = .empty,
+
+    // Reused by `internNext` to collect the code points of a cluster.
+    scratch: std.ArrayList(u21) = .empty,
+
+    pub const Id = u32;
+    pub const max_code_point_id: Id = 0x10FFFF;
+    const first_multi_id: Id = max_code_point_id + 1;
+
+    const Entry = struct {
+        code_points: []u21,
+        ref_count: u32,
+    };
+
+    const CodePointsContext = struct {
+        pub fn hash(_: CodePointsContext, key: []const u21) u64 {
+            var hasher = std.hash.Wyhash.init(0);
+            for (key) |cp| {
+                const value: u32 = cp;
+                hasher.update(std.mem.asBytes(&value));
+            }
+            return hasher.final();
+        }
+
+        pub fn eql(_: CodePointsContext, a: []const u21, b: []const u21) bool {
+            return std.mem.eql(u21, a, b);
+        }
+    };
+
+    pub fn deinit(self: *GraphemeInterner, allocator: std.mem.Allocator) void {
+        for (self.entries.items) |entry| allocator.free(entry.code_points);
+        self.entries.deinit(allocator);
+        self.free_list.deinit(allocator);
+        self.map.deinit(allocator);
+        self.scratch.deinit(allocator);
+        self.* = undefined;
+    }
+
+    // Returns the ID for the grapheme cluster made up of `code_points`,
+    // taking a reference to it if it's a multi code point cluster (see
+    // `release`).
+    pub fn intern(
+        self: *GraphemeInterner,
+        allocator: std.mem.Allocator,
+        code_points: []const u21,
+    ) std.mem.Allocator.Error!Id {
+        inlineAssert(code_points.len > 0);
+        if (code_points.len == 1) return code_points[0];
+
+        if (self.map.get(code_points)) |id| {
+            self.entries.items[id - first_multi_id].ref_count += 1;
+            return id;
+        }
+
+        // Reserve everything up front, so nothing is left half done if an
+        // allocation fails, and `release` doesn't need to allocate.
+        try self.map.ensureUnusedCapacity(allocator, 1);
+        try self.entries.ensureUnusedCapacity(allocator, 1);
+        try self.free_list.ensureTotalCapacity(allocator, self.entries.items.len + 1);
+        const owned = try allocator.dupe(u21, code_points);
+
+        const index = self.free_list.pop() orelse blk: {
+            self.entries.appendAssumeCapacity(undefined);
+            break :blk @as(u32, @intCast(self.entries.items.len - 1));
+        };
+        self.entries.items[index] = .{
+            .code_points = owned,
+            .ref_count = 1,
+        };
+
+        const id = first_multi_id + index;
+        self.map.putAssumeCapacityNoClobber(owned, id);
+        return id;
+    }
+
+    // Interns the next grapheme cluster of the grapheme iterator `it`
+    // (advancing it), so clusters are the same as `grapheme.Iterator` yields.
+    pub fn internNext(
+        self: *GraphemeInterner,
+        allocator: std.mem.Allocator,
+        it: anytype,
+    ) std.mem.Allocator.Error!?Id {
+        inlineAssert(@typeInfo(@TypeOf(it)) == .pointer);
+
+        self.scratch.clearRetainingCapacity();
+        while (it.nextCodePoint()) |result| {
+            try self.scratch.append(allocator, result.code_point);
+            if (result.is_break) break;
+        }
+
+        if (self.scratch.items.len == 0) return null;
+        return try self.intern(allocator, self.scratch.items);
+    }
+
+    // Takes another reference to `id` (a no-op for single code points).
+    pub fn retain(self: *GraphemeInterner, id: Id) void {
+        if (id <= max_code_point_id) return;
+        const entry = &self.entries.items[id - first_multi_id];
+        inlineAssert(entry.ref_count > 0);
+        entry.ref_count += 1;
+    }
+
+    // Releases a reference to `id`, freeing the cluster once there are no
+    // more references (a no-op for single code points).
+    pub fn release(self: *GraphemeInterner, allocator: std.mem.Allocator, id: Id) void {
+        if (id <= max_code_point_id) return;
+        const index = id - first_multi_id;
+        const entry = &self.entries.items[index];
+        inlineAssert(entry.ref_count > 0);
+        entry.ref_count -= 1;
+        if (entry.ref_count > 0) return;
+
+        _ = self.map.remove(entry.code_points);
+        allocator.free(entry.code_points);
+        entry.code_points = &.{};
+        self.free_list.appendAssumeCapacity(index);
+    }
+
+    // Returns the code points of the grapheme cluster `id`. For a single
+    // code point ID, the code point is written to `buffer` and returned as a
+    // slice of it, so `buffer` must outlive the result.
+    pub fn get(self: GraphemeInterner, id: Id, buffer: *[1]u21) []const u21 {
+        if (id <= max_code_point_id) {
+            buffer[0] = @intCast(id);
+            return buffer;
+        }
+        const entry = self.entries.items[id - first_multi_id];
+        inlineAssert(entry.ref_count > 0);
+        return entry.code_points;
+    }
+
+    // The number of multi code point grapheme clusters currently interned.
+    pub fn count(self: GraphemeInterner) usize {
+        return self.map.count();
+    }
+};
+
+test "GraphemeInterner" {
+    const allocator = std.testing.allocator;
+    var interner: GraphemeInterner = .{};
+    defer interner.deinit(allocator);
+
+    try std.testing.expectEqual('a', try interner.intern(allocator, &.{'a'}));
+    try std.testing.expectEqual(0, interner.count());
+
+    const astronaut = [_]u21{ 0x1F469, 0x1F3FD, 0x200D, 0x1F680 };
+    const id = try interner.intern(allocator, &astronaut);
+    try std.testing.expect(id > GraphemeInterner.max_code_point_id);
+    try std.testing.expectEqual(id, try interner.intern(allocator, &astronaut));
+    try std.testing.expectEqual(1, interner.count());
+
+    var buffer: [1]u21 = undefined;
+    try std.testing.expectEqualSlices(u21, &astronaut, interner.get(id, &buffer));
+    const single = interner.get('a', &buffer);
+    try std.testing.expectEqualSlices(u21, &.{'a'}, single);
+
+    const e_acute = try interner.intern(allocator, &.{ 'e', 0x0301 });
+    try std.testing.expect(e_acute != id);
+    try std.testing.expectEqual(2, interner.count());
+
+    // Two references to `id` were taken, plus one more
+    interner.retain(id);
+    interner.release(allocator, id);
+    interner.release(allocator, id);
+    try std.testing.expectEqual(2, interner.count());
+    interner.release(allocator, id);
+    try std.testing.expectEqual(1, interner.count());
+
+    // The freed ID is reused
+    try std.testing.expectEqual(id, try interner.intern(allocator, &.{ 'o', 0x0308 }));
+    interner.release(allocator, 'a');
+}
+
+test "GraphemeInterner internNext" {
+    const allocator = std.testing.allocator;
+    var interner: GraphemeInterner = .{};
+    defer interner.deinit(allocator);
+
+    var it = grapheme.utf8Iterator("e\u{0301}x🇨🇭e\u{0301}");
+    const first = (try interner.internNext(allocator, &it)).?;
+    try std.testing.expectEqual('x', (try interner.internNext(allocator, &it)).?);
+    const flag = (try interner.internNext(allocator, &it)).?;
+    try std.testing.expectEqual(first, (try interner.internNext(allocator, &it)).?);
+    try std.testing.expectEqual(null, try interner.internNext(allocator, &it));
+
+    var buffer: [1]u21 = undefined;
+    try std.testing.expectEqualSlices(u21, &.{ 0x1F1E8, 0x1F1ED }, interner.get(flag, &buffer));
+    try std.testing.expectEqual(2, interner.count());
+}